@@ -0,0 +1,416 @@
+// Fixed-width 256-bit unsigned integer arithmetic for token amounts.
+//
+// RuneSwap and NEAR Intents exchange raw token amounts as decimal strings;
+// an 18-decimal amount can reach 10^27, and scaling it by a price or a
+// power of ten (as the fee and slippage math does) overflows a u128
+// (max ~3.4 * 10^38), so the bare `String` amounts in `types.rs` made slippage
+// checks and profitability math impossible without ad-hoc parsing. `U256`
+// stores a value as four little-endian u64 limbs and implements the
+// checked arithmetic and decimal string conversions the solver needs,
+// without pulling in a full big-integer dependency.
+
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::types::Token;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AmountError {
+    #[error("amount overflowed 256 bits")]
+    Overflow,
+
+    #[error("invalid decimal amount: {0}")]
+    InvalidDecimal(String),
+
+    #[error("amounts have mismatched decimals: {0} vs {1}")]
+    MismatchedDecimals(u8, u8),
+}
+
+/// A 256-bit unsigned integer, stored as four little-endian u64 limbs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct U256([u64; 4]);
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Limbs are little-endian, so compare from the most-significant
+        // limb (index 3) down to the least (index 0); a derived
+        // lexicographic compare would start at index 0 and get the
+        // ordering backwards for any value spanning more than one limb.
+        for i in (0..4).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                std::cmp::Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+        std::cmp::Ordering::Equal
+    }
+}
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+
+    pub fn from_u128(v: u128) -> Self {
+        U256([v as u64, (v >> 64) as u64, 0, 0])
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        (self.0[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    fn set_bit(mut self, i: usize) -> Self {
+        self.0[i / 64] |= 1 << (i % 64);
+        self
+    }
+
+    /// Left shift by one bit, discarding any overflow out of the top limb.
+    fn shl1(self) -> Self {
+        let mut out = [0u64; 4];
+        let mut carry = 0u64;
+        for i in 0..4 {
+            out[i] = (self.0[i] << 1) | carry;
+            carry = self.0[i] >> 63;
+        }
+        U256(out)
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let mut result = [0u64; 4];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let sum = self.0[i] as u128 + rhs.0[i] as u128 + carry;
+            result[i] = sum as u64;
+            carry = sum >> 64;
+        }
+        if carry != 0 {
+            None
+        } else {
+            Some(U256(result))
+        }
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        if self < rhs {
+            return None;
+        }
+        let mut result = [0u64; 4];
+        let mut borrow = 0i128;
+        for i in 0..4 {
+            let diff = self.0[i] as i128 - rhs.0[i] as i128 - borrow;
+            if diff < 0 {
+                result[i] = (diff + (1i128 << 64)) as u64;
+                borrow = 1;
+            } else {
+                result[i] = diff as u64;
+                borrow = 0;
+            }
+        }
+        Some(U256(result))
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // Schoolbook multiplication; any product landing above limb 3
+        // means the 256-bit result overflowed.
+        let mut result = [0u128; 8];
+        for i in 0..4 {
+            if self.0[i] == 0 {
+                continue;
+            }
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let idx = i + j;
+                let product = self.0[i] as u128 * rhs.0[j] as u128 + result[idx] + carry;
+                result[idx] = product & (u64::MAX as u128);
+                carry = product >> 64;
+            }
+            let mut k = i + 4;
+            while carry != 0 {
+                let sum = result[k] + carry;
+                result[k] = sum & (u64::MAX as u128);
+                carry = sum >> 64;
+                k += 1;
+            }
+        }
+        if result[4..].iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().enumerate() {
+            *limb = result[i] as u64;
+        }
+        Some(U256(out))
+    }
+
+    /// Binary long division, returning `(quotient, remainder)`.
+    pub fn checked_div_rem(self, divisor: Self) -> Option<(Self, Self)> {
+        if divisor == U256::ZERO {
+            return None;
+        }
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for i in (0..256).rev() {
+            remainder = remainder.shl1();
+            if self.bit(i) {
+                remainder = remainder.checked_add(U256::from_u128(1))?;
+            }
+            if remainder >= divisor {
+                remainder = remainder.checked_sub(divisor)?;
+                quotient = quotient.set_bit(i);
+            }
+        }
+        Some((quotient, remainder))
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == U256::ZERO {
+            return write!(f, "0");
+        }
+        let ten = U256::from_u128(10);
+        let mut digits = Vec::new();
+        let mut value = *self;
+        while value != U256::ZERO {
+            let (quotient, remainder) = value.checked_div_rem(ten).expect("divisor is non-zero");
+            digits.push(b'0' + remainder.0[0] as u8);
+            value = quotient;
+        }
+        digits.reverse();
+        f.write_str(std::str::from_utf8(&digits).expect("digits are ASCII"))
+    }
+}
+
+impl FromStr for U256 {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AmountError::InvalidDecimal(s.to_string()));
+        }
+        let ten = U256::from_u128(10);
+        let mut value = U256::ZERO;
+        for b in s.bytes() {
+            let digit = U256::from_u128((b - b'0') as u128);
+            value = value.checked_mul(ten).ok_or(AmountError::Overflow)?;
+            value = value.checked_add(digit).ok_or(AmountError::Overflow)?;
+        }
+        Ok(value)
+    }
+}
+
+/// `10^exponent` as a `U256`, or `None` if it overflows 256 bits.
+fn pow10(exponent: u32) -> Option<U256> {
+    let mut result = U256::from_u128(1);
+    let ten = U256::from_u128(10);
+    for _ in 0..exponent {
+        result = result.checked_mul(ten)?;
+    }
+    Some(result)
+}
+
+/// A token amount paired with the decimals needed to render it as a
+/// human-readable decimal string, analogous to how `Token` carries its
+/// own `decimals`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenAmount {
+    pub raw: U256,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(raw: U256, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+
+    /// Parse a raw (integer, not decimal-shifted) amount string for `token`.
+    pub fn from_raw_str(amount: &str, token: &Token) -> Result<Self, AmountError> {
+        let raw = U256::from_str(amount)?;
+        Ok(Self::new(raw, token.decimals))
+    }
+
+    /// Parse a human decimal string such as `"1950.0"` or `"0.00042"`,
+    /// inferring `decimals` from however many digits follow the point.
+    pub fn from_decimal_str(amount: &str) -> Result<Self, AmountError> {
+        let (whole, fraction) = match amount.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (amount, ""),
+        };
+        let decimals = u8::try_from(fraction.len())
+            .map_err(|_| AmountError::InvalidDecimal(amount.to_string()))?;
+        let raw = U256::from_str(&format!("{}{}", whole, fraction))
+            .map_err(|_| AmountError::InvalidDecimal(amount.to_string()))?;
+        Ok(Self::new(raw, decimals))
+    }
+
+    /// Convert this amount into `to_decimals`' terms using `price`, a
+    /// human decimal string giving how many units of the target currency
+    /// one unit of this amount's currency is worth (e.g. converting a gas
+    /// fee denominated in the chain's native currency into a quote's
+    /// `to_token` using the quoted exchange rate between the two).
+    pub fn convert_via_price(&self, price: &str, to_decimals: u8) -> Result<Self, AmountError> {
+        let price = Self::from_decimal_str(price)?;
+        let product = self.raw.checked_mul(price.raw).ok_or(AmountError::Overflow)?;
+
+        let combined_decimals = self.decimals as i32 + price.decimals as i32;
+        let target_decimals = to_decimals as i32;
+        let raw = if target_decimals >= combined_decimals {
+            let scale = pow10(u32::try_from(target_decimals - combined_decimals).unwrap())
+                .ok_or(AmountError::Overflow)?;
+            product.checked_mul(scale).ok_or(AmountError::Overflow)?
+        } else {
+            let scale = pow10(u32::try_from(combined_decimals - target_decimals).unwrap())
+                .ok_or(AmountError::Overflow)?;
+            let (quotient, _) = product.checked_div_rem(scale).ok_or(AmountError::Overflow)?;
+            quotient
+        };
+
+        Ok(Self::new(raw, to_decimals))
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self, AmountError> {
+        if self.decimals != rhs.decimals {
+            return Err(AmountError::MismatchedDecimals(self.decimals, rhs.decimals));
+        }
+        self.raw
+            .checked_add(rhs.raw)
+            .map(|raw| Self::new(raw, self.decimals))
+            .ok_or(AmountError::Overflow)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, AmountError> {
+        if self.decimals != rhs.decimals {
+            return Err(AmountError::MismatchedDecimals(self.decimals, rhs.decimals));
+        }
+        self.raw
+            .checked_sub(rhs.raw)
+            .map(|raw| Self::new(raw, self.decimals))
+            .ok_or(AmountError::Overflow)
+    }
+
+    pub fn checked_mul(self, rhs: U256) -> Result<Self, AmountError> {
+        self.raw
+            .checked_mul(rhs)
+            .map(|raw| Self::new(raw, self.decimals))
+            .ok_or(AmountError::Overflow)
+    }
+
+    /// Whether this amount meets or exceeds `min`, given matching decimals.
+    pub fn meets_minimum(&self, min: &TokenAmount) -> bool {
+        self.decimals == min.decimals && self.raw >= min.raw
+    }
+
+    /// The amount this one exceeds `min` by, in basis points of `min`.
+    /// Returns `None` if `min` is zero, the amounts don't share decimals,
+    /// or this amount is below `min`.
+    pub fn slippage_bps(&self, min: &TokenAmount) -> Option<i64> {
+        if self.decimals != min.decimals || min.raw == U256::ZERO {
+            return None;
+        }
+        let diff = self.raw.checked_sub(min.raw)?;
+        let (bps, _) = diff
+            .checked_mul(U256::from_u128(10_000))?
+            .checked_div_rem(min.raw)?;
+        Some(bps.0[0] as i64)
+    }
+
+    /// Render the raw integer amount as a decimal string honoring
+    /// `decimals`, e.g. `raw=1_500_000, decimals=6` -> `"1.5"`.
+    pub fn to_decimal_string(&self) -> String {
+        let raw = self.raw.to_string();
+        if self.decimals == 0 {
+            return raw;
+        }
+        let decimals = self.decimals as usize;
+        let (whole, fraction) = if raw.len() <= decimals {
+            ("0".to_string(), format!("{:0>width$}", raw, width = decimals))
+        } else {
+            let split = raw.len() - decimals;
+            (raw[..split].to_string(), raw[split..].to_string())
+        };
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            whole
+        } else {
+            format!("{}.{}", whole, fraction)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_displays_large_amounts() {
+        let value = U256::from_str("1000000000000000000").unwrap();
+        assert_eq!(value.to_string(), "1000000000000000000");
+    }
+
+    #[test]
+    fn ordering_compares_limbs_from_most_significant_down() {
+        // 20e18 overflows the first u64 limb (max ~18.4e18) into the
+        // second, so a derived lexicographic (least-significant-first)
+        // compare would wrongly rank it below 5e18.
+        let small = U256::from_u128(5_000_000_000_000_000_000);
+        let large = U256::from_u128(20_000_000_000_000_000_000);
+
+        assert!(small < large);
+        assert!(large > small);
+        assert_eq!(small.checked_sub(large), None);
+        assert!(large.checked_sub(small).is_some());
+    }
+
+    #[test]
+    fn to_decimal_string_honors_decimals() {
+        let amount = TokenAmount::new(U256::from_u128(1_500_000), 6);
+        assert_eq!(amount.to_decimal_string(), "1.5");
+
+        let dust = TokenAmount::new(U256::from_u128(5), 6);
+        assert_eq!(dust.to_decimal_string(), "0.000005");
+    }
+
+    #[test]
+    fn meets_minimum_respects_decimals_and_magnitude() {
+        let amount = TokenAmount::new(U256::from_u128(100), 6);
+        let min = TokenAmount::new(U256::from_u128(90), 6);
+        assert!(amount.meets_minimum(&min));
+        assert!(!min.meets_minimum(&amount));
+
+        let mismatched = TokenAmount::new(U256::from_u128(100), 8);
+        assert!(!mismatched.meets_minimum(&min));
+    }
+
+    #[test]
+    fn slippage_bps_computes_basis_points_above_minimum() {
+        let amount = TokenAmount::new(U256::from_u128(1_010), 0);
+        let min = TokenAmount::new(U256::from_u128(1_000), 0);
+        assert_eq!(amount.slippage_bps(&min), Some(100));
+    }
+
+    #[test]
+    fn convert_via_price_rescales_between_currencies_and_decimals() {
+        // 0.01 ETH (18 decimals) worth of gas, at a quoted price of 1950
+        // USDC per ETH, should convert to 19.5 USDC (6 decimals).
+        let fee_eth = TokenAmount::new(U256::from_u128(10_000_000_000_000_000), 18);
+        let fee_usdc = fee_eth.convert_via_price("1950.0", 6).unwrap();
+        assert_eq!(fee_usdc.decimals, 6);
+        assert_eq!(fee_usdc.to_decimal_string(), "19.5");
+    }
+
+    #[test]
+    fn from_decimal_str_infers_decimals_from_fraction_digits() {
+        let parsed = TokenAmount::from_decimal_str("1950.25").unwrap();
+        assert_eq!(parsed.decimals, 2);
+        assert_eq!(parsed.raw, U256::from_u128(195_025));
+
+        let whole = TokenAmount::from_decimal_str("42").unwrap();
+        assert_eq!(whole.decimals, 0);
+        assert_eq!(whole.raw, U256::from_u128(42));
+    }
+}
@@ -0,0 +1,489 @@
+// Subscription driver for the solver bus.
+//
+// The solver bus protocol types (`SolverBusMessage`, `SolverBusParams`,
+// `JsonRpcRequest`, `JsonRpcResponse`) already existed in `types.rs`, but
+// nothing owned a resilient connection to `Config::solver_bus_url`.
+// `SolverBus` does: it subscribes, decodes inbound intents, reconnects
+// with exponential backoff when the connection drops, deduplicates
+// intents by `id` across reconnects so a swap isn't executed twice, and
+// forwards each intent exactly once over a `tokio::sync::mpsc` channel
+// for the solver to consume. A `SolverBusHandle` lets callers publish
+// requests (e.g. a quote) back onto the bus and await the matching
+// JSON-RPC response, correlated by request id. The handle outlives
+// reconnects; a request still awaiting its reply when a connection drops
+// fails instead of carrying over to the next connection.
+//
+// The actual socket is provided by a `crate::transport::Transport`
+// (WebSocket or local IPC, picked from the URL scheme), so none of the
+// above needs to know which one is in use.
+
+use crate::transport::{self, BusReader};
+use crate::types::{JsonRpcRequest, JsonRpcResponse, SolverBusMessage, SwapIntent, SwapQuote};
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Initial delay before the first reconnect attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound on the reconnect delay.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// How long a connection must stay up before a subsequent drop resets the
+/// backoff back to `initial_backoff`, instead of continuing to climb.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// How often to ping the bus to keep the connection alive.
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+/// Capacity of the channel of decoded intents handed to the solver.
+const INTENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Tunable parameters for the reconnect supervisor in [`SolverBus::run`].
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnect attempt after a drop.
+    pub initial_backoff: Duration,
+    /// Upper bound the backoff doubles up to.
+    pub max_backoff: Duration,
+    /// How long a connection must stay up before the backoff resets to
+    /// `initial_backoff` on the next drop.
+    pub stable_after: Duration,
+    /// Maximum consecutive reconnect attempts before giving up entirely.
+    /// `None` retries forever.
+    pub max_retries: Option<u32>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: INITIAL_BACKOFF,
+            max_backoff: MAX_BACKOFF,
+            stable_after: STABLE_AFTER,
+            max_retries: None,
+        }
+    }
+}
+
+/// Tunable parameters for ping/pong liveness detection.
+#[derive(Debug, Clone)]
+pub struct LivenessConfig {
+    /// How often to ping the bus to keep the connection alive.
+    pub ping_interval: Duration,
+    /// How long to go without receiving a pong (or any other inbound
+    /// frame) before the connection is treated as dead.
+    pub pong_timeout: Duration,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: PING_INTERVAL,
+            pong_timeout: PING_INTERVAL * 2,
+        }
+    }
+}
+
+/// A cloneable handle for enqueuing outbound frames on the current solver
+/// bus connection. Backed by an unbounded channel drained by a dedicated
+/// writer task, so publishing a frame never waits on (or blocks) the read
+/// loop that decodes inbound intents and responses.
+#[derive(Clone)]
+struct SolverConnection {
+    tx: mpsc::UnboundedSender<Message>,
+}
+
+impl SolverConnection {
+    fn send(&self, msg: Message) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.tx
+            .send(msg)
+            .map_err(|_| "solver bus connection is closed".into())
+    }
+}
+
+/// State shared between the background reconnect loop and
+/// [`SolverBusHandle`]s so handles survive a reconnect and new outbound
+/// requests get routed to whichever connection is currently live.
+struct BusShared {
+    /// Outbound requests awaiting a matching `JsonRpcResponse`, keyed by
+    /// request id.
+    pending: Mutex<BTreeMap<u64, oneshot::Sender<JsonRpcResponse>>>,
+    /// Request id counter. Starts at 2 since id 1 is reserved for the
+    /// `subscribe` request sent on every connect.
+    next_id: AtomicU64,
+    /// The current connection, if connected.
+    connection: Mutex<Option<SolverConnection>>,
+}
+
+impl BusShared {
+    fn new() -> Self {
+        Self {
+            pending: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(2),
+            connection: Mutex::new(None),
+        }
+    }
+}
+
+/// A cloneable handle for publishing requests onto the solver bus and
+/// awaiting the bus's response, independent of the background reconnect
+/// loop that owns the actual socket.
+#[derive(Clone)]
+pub struct SolverBusHandle {
+    shared: Arc<BusShared>,
+}
+
+impl SolverBusHandle {
+    /// Publish `quote` back to the solver bus as a `quote` JSON-RPC
+    /// request and wait for the bus to acknowledge it.
+    pub async fn send_quote(&self, quote: &SwapQuote) -> Result<JsonRpcResponse, Box<dyn Error + Send + Sync>> {
+        let id = self.shared.next_id.fetch_add(1, Ordering::SeqCst);
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id,
+            method: "quote".to_string(),
+            params: Some(vec![serde_json::to_string(quote)?]),
+        };
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.shared.pending.lock().unwrap().insert(id, response_tx);
+
+        let connection = self.shared.connection.lock().unwrap().clone();
+        let connection = match connection {
+            Some(connection) => connection,
+            None => {
+                self.shared.pending.lock().unwrap().remove(&id);
+                return Err("not connected to the solver bus".into());
+            }
+        };
+
+        if let Err(e) = connection.send(Message::Text(serde_json::to_string(&request)?)) {
+            self.shared.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        response_rx
+            .await
+            .map_err(|_| "solver bus connection dropped before replying".into())
+    }
+}
+
+/// Drives a connection to the solver bus over whichever
+/// [`crate::transport::Transport`] matches `url`'s scheme: subscribes,
+/// decodes incoming intents, and reconnects with exponential backoff on
+/// drops.
+pub struct SolverBus {
+    url: String,
+    reconnect: ReconnectConfig,
+    liveness: LivenessConfig,
+    shared: Arc<BusShared>,
+}
+
+impl SolverBus {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            reconnect: ReconnectConfig::default(),
+            liveness: LivenessConfig::default(),
+            shared: Arc::new(BusShared::new()),
+        }
+    }
+
+    /// Use `reconnect` instead of the default backoff parameters.
+    pub fn with_reconnect_config(mut self, reconnect: ReconnectConfig) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Use `liveness` instead of the default ping/pong timing.
+    pub fn with_liveness_config(mut self, liveness: LivenessConfig) -> Self {
+        self.liveness = liveness;
+        self
+    }
+
+    /// Connect to the solver bus and spawn a background task that feeds
+    /// decoded, deduplicated intents into the returned channel. The task
+    /// keeps reconnecting until the receiver is dropped. The returned
+    /// handle can be used to publish requests (e.g. a quote) back onto
+    /// the bus regardless of reconnects.
+    pub fn subscribe(self) -> (SolverBusHandle, mpsc::Receiver<SwapIntent>) {
+        let handle = SolverBusHandle {
+            shared: self.shared.clone(),
+        };
+        let (tx, rx) = mpsc::channel(INTENT_CHANNEL_CAPACITY);
+        tokio::spawn(self.run(tx));
+        (handle, rx)
+    }
+
+    async fn run(self, tx: mpsc::Sender<SwapIntent>) {
+        let mut seen_intent_ids = HashSet::new();
+        let mut backoff = self.reconnect.initial_backoff;
+        let mut consecutive_failures = 0u32;
+
+        loop {
+            let attempt_started = Instant::now();
+            match self.connect_and_process(&tx, &mut seen_intent_ids).await {
+                Ok(()) => {
+                    // The receiver was dropped; nothing left to feed.
+                    log::info!("Solver bus subscriber gone, stopping");
+                    return;
+                }
+                Err(e) => {
+                    if attempt_started.elapsed() >= self.reconnect.stable_after {
+                        // The connection was up long enough to count as
+                        // stable; don't let a single late drop inherit a
+                        // climbed-up backoff from earlier flakiness.
+                        backoff = self.reconnect.initial_backoff;
+                        consecutive_failures = 0;
+                    } else {
+                        consecutive_failures += 1;
+                        if let Some(max_retries) = self.reconnect.max_retries {
+                            if consecutive_failures > max_retries {
+                                log::error!(
+                                    "Solver bus giving up after {} consecutive failed reconnect attempts",
+                                    consecutive_failures
+                                );
+                                return;
+                            }
+                        }
+                    }
+
+                    log::error!(
+                        "Solver bus connection lost: {}. Reconnecting in {:?}",
+                        e,
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, self.reconnect.max_backoff);
+                }
+            }
+        }
+    }
+
+    /// Connect once, subscribe, and process messages until the connection
+    /// drops or the receiving end goes away (in which case `Ok(())` is
+    /// returned so the caller doesn't keep reconnecting).
+    async fn connect_and_process(
+        &self,
+        tx: &mpsc::Sender<SwapIntent>,
+        seen_intent_ids: &mut HashSet<String>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        log::info!("Connecting to solver bus at: {}", self.url);
+        let transport = transport::for_url(&self.url);
+        let (mut write, mut read) = transport.connect(&self.url).await?;
+        log::info!("Connected to solver bus");
+
+        // The writer task owns the write half, so publishing (quotes,
+        // pings, pong replies) never has to wait for the read loop to
+        // come back around its select.
+        let (outbound_tx, mut outbound_rx) = mpsc::unbounded_channel::<Message>();
+        let connection = SolverConnection { tx: outbound_tx };
+        *self.shared.connection.lock().unwrap() = Some(connection.clone());
+
+        let writer = tokio::spawn(async move {
+            while let Some(msg) = outbound_rx.recv().await {
+                if let Err(e) = write.send(msg).await {
+                    log::error!("Solver bus write failed: {}", e);
+                    break;
+                }
+            }
+        });
+
+        let subscribe_request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: 1,
+            method: "subscribe".to_string(),
+            params: Some(vec!["intents".to_string()]),
+        };
+        connection.send(Message::Text(serde_json::to_string(&subscribe_request)?))?;
+
+        let result = self
+            .process_messages(read.as_mut(), tx, seen_intent_ids, &connection)
+            .await;
+
+        // This connection is no longer usable; drop its handle so
+        // `SolverBusHandle::send_quote` fails fast instead of silently
+        // queuing into a dead writer until the next reconnect succeeds,
+        // then abort the writer task; frames still queued for this
+        // connection are discarded.
+        *self.shared.connection.lock().unwrap() = None;
+        drop(connection);
+        writer.abort();
+
+        // Drop every response waiter still parked from this connection too;
+        // otherwise a quote sent right before the drop never gets a reply,
+        // and its `send_quote` call hangs forever instead of erroring out.
+        // Dropping the sender resolves the paired `response_rx.await` with
+        // `RecvError`, which `send_quote` already turns into a clean error.
+        self.shared.pending.lock().unwrap().clear();
+
+        result
+    }
+
+    /// Drive one live connection: dispatch inbound intents and JSON-RPC
+    /// responses, and keep it alive with ping/pong liveness checks.
+    /// Publishing happens independently through `connection`/the writer
+    /// task, so this loop only ever reads. Returns once the connection
+    /// drops, the receiver is gone, or the connection is judged dead.
+    async fn process_messages(
+        &self,
+        read: &mut dyn BusReader,
+        tx: &mpsc::Sender<SwapIntent>,
+        seen_intent_ids: &mut HashSet<String>,
+        connection: &SolverConnection,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut interval = tokio::time::interval(self.liveness.ping_interval);
+        let mut last_traffic = Instant::now();
+        let mut unanswered_pings = 0u32;
+
+        loop {
+            tokio::select! {
+                msg = read.recv() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            last_traffic = Instant::now();
+                            unanswered_pings = 0;
+                            log::debug!("Received message: {}", text);
+                            if let Ok(response) = serde_json::from_str::<JsonRpcResponse>(&text) {
+                                match self.shared.pending.lock().unwrap().remove(&response.id) {
+                                    Some(waiter) => {
+                                        let _ = waiter.send(response);
+                                    }
+                                    None => log::debug!(
+                                        "Ignoring response for unknown request id: {}",
+                                        response.id
+                                    ),
+                                }
+                            } else if let Ok(solver_msg) = serde_json::from_str::<SolverBusMessage>(&text) {
+                                if solver_msg.method == "subscription" {
+                                    if let Some(intent) = solver_msg.params.intent {
+                                        if seen_intent_ids.insert(intent.id.clone()) {
+                                            if tx.send(intent).await.is_err() {
+                                                return Ok(());
+                                            }
+                                        } else {
+                                            log::debug!("Ignoring duplicate intent: {}", intent.id);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        Some(Ok(Message::Pong(_))) => {
+                            last_traffic = Instant::now();
+                            unanswered_pings = 0;
+                        }
+                        Some(Ok(Message::Ping(data))) => {
+                            last_traffic = Instant::now();
+                            unanswered_pings = 0;
+                            connection.send(Message::Pong(data))?;
+                        }
+                        Some(Ok(Message::Close(_))) => {
+                            return Err("solver bus closed the connection".into());
+                        }
+                        Some(Err(e)) => return Err(e),
+                        None => return Err("solver bus stream ended unexpectedly".into()),
+                        _ => { /* ignore other frame types */ }
+                    }
+                }
+                _ = interval.tick() => {
+                    if last_traffic.elapsed() >= self.liveness.pong_timeout {
+                        return Err(format!(
+                            "no traffic from solver bus in {:?} ({} unanswered pings), treating connection as dead",
+                            last_traffic.elapsed(),
+                            unanswered_pings
+                        )
+                        .into());
+                    }
+                    unanswered_pings += 1;
+                    connection.send(Message::Ping(vec![]))?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    #[tokio::test]
+    async fn decodes_and_dispatches_subscribed_intents() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+
+            // Drain the subscribe request the bus sends on connect.
+            let _ = ws.next().await;
+
+            for id in ["intent-1", "intent-2"] {
+                let frame = format!(
+                    r#"{{"jsonrpc":"2.0","method":"subscription","params":{{"subscription":"intents","id":"{id}","from_token":{{"symbol":"ETH","address":"0xETH","decimals":18}},"to_token":{{"symbol":"USDC","address":"0xUSDC","decimals":6}},"amount":"1000000000000000000","min_amount_out":"1900000000","deadline":1682661234}}}}"#,
+                    id = id
+                );
+                ws.send(Message::Text(frame)).await.unwrap();
+            }
+        });
+
+        let bus = SolverBus::new(format!("ws://{}", addr));
+        let (_handle, mut rx) = bus.subscribe();
+
+        let first = rx.recv().await.unwrap();
+        let second = rx.recv().await.unwrap();
+
+        assert_eq!(first.id, "intent-1");
+        assert_eq!(second.id, "intent-2");
+        assert_eq!(first.from_token.symbol, "ETH");
+    }
+
+    #[tokio::test]
+    async fn send_quote_errors_instead_of_hanging_when_the_connection_drops_mid_flight() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+
+            // Drain the subscribe request, then read the quote request and
+            // close the connection without ever replying to it.
+            let _ = ws.next().await;
+            let _ = ws.next().await;
+            let _ = ws.close(None).await;
+        });
+
+        let bus = SolverBus::new(format!("ws://{}", addr));
+        let (handle, _rx) = bus.subscribe();
+
+        let quote = SwapQuote {
+            intent_id: "intent-1".to_string(),
+            amount_out: "1900000000".to_string(),
+            price: "1900.0".to_string(),
+            gas_estimate: 21000,
+            slippage_bps: 0,
+            expires_at: 0,
+        };
+
+        // Wait until the connection is up, so the quote is actually in
+        // flight when the server drops it rather than rejected up front.
+        for _ in 0..250 {
+            if handle.shared.connection.lock().unwrap().is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(handle.shared.connection.lock().unwrap().is_some());
+
+        // send_quote must resolve with an error once the server drops the
+        // connection, rather than hanging forever waiting on a response
+        // that will now never arrive.
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), handle.send_quote(&quote)).await;
+        assert!(result.expect("send_quote should not hang").is_err());
+    }
+}
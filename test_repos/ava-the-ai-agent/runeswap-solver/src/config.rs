@@ -19,15 +19,20 @@ pub enum ConfigError {
 pub struct Config {
     /// API key for the RuneSwap service
     pub runeswap_api_key: String,
-    
+
     /// Account ID for the NEAR blockchain
     pub near_account_id: String,
-    
+
     /// Private key for the NEAR account
     pub near_private_key: String,
-    
-    /// URL for the solver bus
+
+    /// URL for the solver bus. Scheme selects the transport: `ws://`/
+    /// `wss://` for a WebSocket connection, `unix://` or `npipe://` for a
+    /// local IPC socket (see `transport::for_url`).
     pub solver_bus_url: String,
+
+    /// Configuration for the gas/price oracle that nets fees out of quotes
+    pub gas_oracle: GasOracleConfig,
 }
 
 impl Config {
@@ -43,28 +48,101 @@ impl Config {
             near_account_id,
             near_private_key,
             solver_bus_url,
+            gas_oracle: GasOracleConfig::default(),
         }
     }
-    
+
     /// Create a configuration from environment variables
     pub fn from_env() -> Result<Self, Box<dyn Error>> {
         let runeswap_api_key = env::var("RUNESWAP_API_KEY")
             .map_err(|_| ConfigError::MissingEnv("RUNESWAP_API_KEY".to_string()))?;
-            
+
         let near_account_id = env::var("NEAR_ACCOUNT_ID")
             .map_err(|_| ConfigError::MissingEnv("NEAR_ACCOUNT_ID".to_string()))?;
-            
+
         let near_private_key = env::var("NEAR_PRIVATE_KEY")
             .map_err(|_| ConfigError::MissingEnv("NEAR_PRIVATE_KEY".to_string()))?;
-            
+
         let solver_bus_url = env::var("SOLVER_BUS_URL")
             .unwrap_or_else(|_| "wss://solver-bus.runeswap.io".to_string());
-        
-        Ok(Self::new(
+
+        Ok(Self {
             runeswap_api_key,
             near_account_id,
             near_private_key,
             solver_bus_url,
-        ))
+            gas_oracle: GasOracleConfig::from_env(),
+        })
     }
-} 
\ No newline at end of file
+}
+
+/// Configuration for the pluggable gas/price oracle used to net fees out
+/// of a quote before it's checked against `SwapIntent::min_amount_out`.
+#[derive(Debug, Clone)]
+pub struct GasOracleConfig {
+    /// HTTP endpoints polled for a live gas price. Queried concurrently
+    /// and combined via the median when more than one is configured, so a
+    /// single bad source can't skew the result.
+    pub oracle_urls: Vec<String>,
+
+    /// Gas price used when no oracle URLs are configured.
+    pub static_price: u64,
+
+    /// How often a polling oracle refreshes its cached price.
+    pub refresh_interval_secs: u64,
+
+    /// How old a cached price may get before a dead oracle surfaces an
+    /// error instead of an arbitrarily stale price.
+    pub max_staleness_secs: u64,
+}
+
+impl Default for GasOracleConfig {
+    fn default() -> Self {
+        Self {
+            oracle_urls: Vec::new(),
+            static_price: 1,
+            refresh_interval_secs: 30,
+            max_staleness_secs: 120,
+        }
+    }
+}
+
+impl GasOracleConfig {
+    /// Read oracle settings from the environment, falling back to
+    /// `Default` for anything unset.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        let oracle_urls = env::var("GAS_ORACLE_URLS")
+            .ok()
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or(defaults.oracle_urls);
+
+        let static_price = env::var("GAS_ORACLE_STATIC_PRICE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.static_price);
+
+        let refresh_interval_secs = env::var("GAS_ORACLE_REFRESH_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.refresh_interval_secs);
+
+        let max_staleness_secs = env::var("GAS_ORACLE_MAX_STALENESS_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.max_staleness_secs);
+
+        Self {
+            oracle_urls,
+            static_price,
+            refresh_interval_secs,
+            max_staleness_secs,
+        }
+    }
+}
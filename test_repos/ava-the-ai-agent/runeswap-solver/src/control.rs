@@ -0,0 +1,182 @@
+// Embedded JSON-RPC control server for solver inspection and manual
+// operation.
+//
+// Mirrors the RPC-server pattern used by atomic swap daemons: pointing a
+// JSON-RPC client at this server lets an operator inspect the running
+// solver (`get_status`, `list_active_intents`) or drive it manually
+// (`get_quote`, `execute_swap`, `list_supported_tokens`) instead of only
+// reading logs. Requests are newline-delimited JSON over a plain TCP
+// socket, started from `main.rs` behind the `--rpc` flag.
+
+use crate::runeswap::SolverMiddleware;
+use crate::swap_state::SwapStateMachine;
+use crate::types::{SwapIntent, SwapQuote};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcResponse {
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecuteSwapParams {
+    intent: SwapIntent,
+    quote: SwapQuote,
+}
+
+/// Live state exposed to the control server by the running solver.
+pub struct ControlState {
+    pub client: Arc<dyn SolverMiddleware>,
+    pub swap_states: Arc<SwapStateMachine>,
+}
+
+/// Embedded JSON-RPC server exposing a running solver's state to operators.
+pub struct ControlServer {
+    state: Arc<ControlState>,
+}
+
+impl ControlServer {
+    pub fn new(state: Arc<ControlState>) -> Self {
+        Self { state }
+    }
+
+    /// Bind `addr` and serve requests until the process exits.
+    pub async fn run(self, addr: &str) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        log::info!("Control server listening on {}", addr);
+
+        loop {
+            let (socket, peer) = listener.accept().await?;
+            log::debug!("Control server connection from {}", peer);
+            let state = self.state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(socket, state).await {
+                    log::warn!("Control server connection error: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(
+    socket: TcpStream,
+    state: Arc<ControlState>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<RpcRequest>(&line) {
+            Ok(request) => dispatch(&state, request).await,
+            Err(e) => RpcResponse {
+                id: Value::Null,
+                result: None,
+                error: Some(format!("invalid request: {}", e)),
+            },
+        };
+
+        let mut out = serde_json::to_string(&response)?;
+        out.push('\n');
+        writer.write_all(out.as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+async fn dispatch(state: &ControlState, request: RpcRequest) -> RpcResponse {
+    let result = match request.method.as_str() {
+        "get_status" => Ok(serde_json::json!({ "status": "running" })),
+        "list_active_intents" => list_active_intents(state),
+        "get_quote" => get_quote(state, request.params).await,
+        "execute_swap" => execute_swap(state, request.params).await,
+        "list_supported_tokens" => list_supported_tokens(state).await,
+        other => Err(format!("unknown method: {}", other)),
+    };
+
+    match result {
+        Ok(value) => RpcResponse {
+            id: request.id,
+            result: Some(value),
+            error: None,
+        },
+        Err(e) => RpcResponse {
+            id: request.id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+fn list_active_intents(state: &ControlState) -> Result<Value, String> {
+    let intents: Vec<Value> = state
+        .swap_states
+        .all()
+        .into_iter()
+        .map(|record| {
+            serde_json::json!({
+                "intent_id": record.intent_id,
+                "state": format!("{:?}", record.state),
+                "intent_deadline": record.intent_deadline,
+                "quote_expires_at": record.quote_expires_at,
+            })
+        })
+        .collect();
+
+    Ok(Value::Array(intents))
+}
+
+async fn get_quote(state: &ControlState, params: Value) -> Result<Value, String> {
+    let intent: SwapIntent =
+        serde_json::from_value(params).map_err(|e| format!("invalid intent: {}", e))?;
+
+    let quote = state
+        .client
+        .get_quote(&intent)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    serde_json::to_value(quote).map_err(|e| e.to_string())
+}
+
+async fn execute_swap(state: &ControlState, params: Value) -> Result<Value, String> {
+    let params: ExecuteSwapParams =
+        serde_json::from_value(params).map_err(|e| format!("invalid params: {}", e))?;
+
+    let tx_id = state
+        .client
+        .execute_swap(&params.intent, &params.quote)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(serde_json::json!({ "transaction_id": tx_id }))
+}
+
+async fn list_supported_tokens(state: &ControlState) -> Result<Value, String> {
+    let tokens = state
+        .client
+        .get_supported_tokens()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    serde_json::to_value(tokens).map_err(|e| e.to_string())
+}
@@ -0,0 +1,279 @@
+// Pluggable gas/price oracles for netting fees out of a quote.
+//
+// `GasOracleMiddleware` used to be a pure pass-through: a quote's
+// `amount_out` was accepted as-is even though gas fees can eat into it
+// enough to violate `SwapIntent::min_amount_out` once fees are accounted
+// for. `GasOracle` abstracts where a live gas price comes from (a fixed
+// value, a single polling HTTP endpoint, or the median of several to
+// resist an outlier source) so the middleware can compute a true net
+// output before deciding whether to forward the swap.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::config::GasOracleConfig;
+
+#[derive(Debug, Error)]
+pub enum GasOracleError {
+    #[error("gas oracle request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("gas price is stale: last refreshed {elapsed_secs}s ago, max age is {max_age_secs}s")]
+    Stale {
+        elapsed_secs: u64,
+        max_age_secs: u64,
+    },
+
+    #[error("no gas oracle sources returned a price")]
+    NoSources,
+}
+
+/// A source of live gas/price data, in the smallest unit of gas price the
+/// caller expects (e.g. wei per gas unit).
+#[async_trait]
+pub trait GasOracle: Send + Sync {
+    async fn gas_price(&self) -> Result<u64, GasOracleError>;
+}
+
+/// Builds the oracle described by `config`: a fixed price when no oracle
+/// URLs are configured, a single polling oracle for one URL, or a
+/// median-of-N aggregator across several.
+pub fn from_config(config: &GasOracleConfig) -> Arc<dyn GasOracle> {
+    if config.oracle_urls.is_empty() {
+        return Arc::new(StaticGasOracle::new(config.static_price));
+    }
+
+    let sources: Vec<Arc<dyn GasOracle>> = config
+        .oracle_urls
+        .iter()
+        .map(|url| {
+            Arc::new(HttpPollingGasOracle::new(
+                url.clone(),
+                Duration::from_secs(config.refresh_interval_secs),
+                Duration::from_secs(config.max_staleness_secs),
+            )) as Arc<dyn GasOracle>
+        })
+        .collect();
+
+    if sources.len() == 1 {
+        sources.into_iter().next().unwrap()
+    } else {
+        Arc::new(MedianGasOracle::new(sources))
+    }
+}
+
+/// A fixed gas price, for deployments that don't want to depend on a live
+/// oracle.
+pub struct StaticGasOracle {
+    price: u64,
+}
+
+impl StaticGasOracle {
+    pub fn new(price: u64) -> Self {
+        Self { price }
+    }
+}
+
+#[async_trait]
+impl GasOracle for StaticGasOracle {
+    async fn gas_price(&self) -> Result<u64, GasOracleError> {
+        Ok(self.price)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GasPriceResponse {
+    gas_price: u64,
+}
+
+struct CachedPrice {
+    price: u64,
+    fetched_at: Instant,
+}
+
+/// Polls an HTTP endpoint for a live gas price, refreshing at most once per
+/// `refresh_interval` and serving the cached value in between. If a refresh
+/// fails, the cached value is still served as long as it's younger than
+/// `max_age`; past that, a dead oracle surfaces an error instead of an
+/// arbitrarily old price.
+pub struct HttpPollingGasOracle {
+    client: Client,
+    url: String,
+    refresh_interval: Duration,
+    max_age: Duration,
+    cached: Mutex<Option<CachedPrice>>,
+}
+
+impl HttpPollingGasOracle {
+    pub fn new(url: String, refresh_interval: Duration, max_age: Duration) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+            refresh_interval,
+            max_age,
+            cached: Mutex::new(None),
+        }
+    }
+
+    async fn fetch(&self) -> Result<u64, GasOracleError> {
+        let response = self
+            .client
+            .get(&self.url)
+            .send()
+            .await?
+            .json::<GasPriceResponse>()
+            .await?;
+        Ok(response.gas_price)
+    }
+}
+
+#[async_trait]
+impl GasOracle for HttpPollingGasOracle {
+    async fn gas_price(&self) -> Result<u64, GasOracleError> {
+        let needs_refresh = {
+            let cached = self.cached.lock().unwrap();
+            match cached.as_ref() {
+                Some(c) => c.fetched_at.elapsed() >= self.refresh_interval,
+                None => true,
+            }
+        };
+
+        if !needs_refresh {
+            return Ok(self.cached.lock().unwrap().as_ref().unwrap().price);
+        }
+
+        match self.fetch().await {
+            Ok(price) => {
+                *self.cached.lock().unwrap() = Some(CachedPrice {
+                    price,
+                    fetched_at: Instant::now(),
+                });
+                Ok(price)
+            }
+            Err(e) => {
+                let cached = self.cached.lock().unwrap();
+                match cached.as_ref() {
+                    Some(c) if c.fetched_at.elapsed() < self.max_age => {
+                        log::warn!(
+                            "Gas oracle {} refresh failed ({}), serving cached price {}s old",
+                            self.url,
+                            e,
+                            c.fetched_at.elapsed().as_secs()
+                        );
+                        Ok(c.price)
+                    }
+                    Some(c) => Err(GasOracleError::Stale {
+                        elapsed_secs: c.fetched_at.elapsed().as_secs(),
+                        max_age_secs: self.max_age.as_secs(),
+                    }),
+                    None => Err(e),
+                }
+            }
+        }
+    }
+}
+
+/// Queries several oracle sources concurrently and takes the median,
+/// resisting a single source that's stale, misbehaving, or manipulated.
+pub struct MedianGasOracle {
+    sources: Vec<Arc<dyn GasOracle>>,
+}
+
+impl MedianGasOracle {
+    pub fn new(sources: Vec<Arc<dyn GasOracle>>) -> Self {
+        Self { sources }
+    }
+}
+
+#[async_trait]
+impl GasOracle for MedianGasOracle {
+    async fn gas_price(&self) -> Result<u64, GasOracleError> {
+        let results =
+            futures_util::future::join_all(self.sources.iter().map(|source| source.gas_price()))
+                .await;
+        let mut prices = Vec::with_capacity(results.len());
+        for result in results {
+            match result {
+                Ok(price) => prices.push(price),
+                Err(e) => log::warn!("Gas oracle source failed: {}", e),
+            }
+        }
+
+        if prices.is_empty() {
+            return Err(GasOracleError::NoSources);
+        }
+
+        prices.sort_unstable();
+        let mid = prices.len() / 2;
+        if prices.len() % 2 == 0 {
+            Ok((prices[mid - 1] + prices[mid]) / 2)
+        } else {
+            Ok(prices[mid])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedOracle(u64);
+
+    #[async_trait]
+    impl GasOracle for FixedOracle {
+        async fn gas_price(&self) -> Result<u64, GasOracleError> {
+            Ok(self.0)
+        }
+    }
+
+    struct FailingOracle;
+
+    #[async_trait]
+    impl GasOracle for FailingOracle {
+        async fn gas_price(&self) -> Result<u64, GasOracleError> {
+            Err(GasOracleError::NoSources)
+        }
+    }
+
+    #[tokio::test]
+    async fn static_oracle_returns_its_configured_price() {
+        let oracle = StaticGasOracle::new(42);
+        assert_eq!(oracle.gas_price().await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn median_oracle_takes_the_middle_value() {
+        let oracle = MedianGasOracle::new(vec![
+            Arc::new(FixedOracle(10)),
+            Arc::new(FixedOracle(30)),
+            Arc::new(FixedOracle(20)),
+        ]);
+
+        assert_eq!(oracle.gas_price().await.unwrap(), 20);
+    }
+
+    #[tokio::test]
+    async fn median_oracle_ignores_failing_sources() {
+        let oracle = MedianGasOracle::new(vec![
+            Arc::new(FixedOracle(10)),
+            Arc::new(FailingOracle),
+            Arc::new(FixedOracle(20)),
+        ]);
+
+        assert_eq!(oracle.gas_price().await.unwrap(), 15);
+    }
+
+    #[tokio::test]
+    async fn median_oracle_errors_when_all_sources_fail() {
+        let oracle = MedianGasOracle::new(vec![Arc::new(FailingOracle), Arc::new(FailingOracle)]);
+
+        assert!(matches!(
+            oracle.gas_price().await,
+            Err(GasOracleError::NoSources)
+        ));
+    }
+}
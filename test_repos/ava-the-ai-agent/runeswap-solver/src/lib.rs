@@ -1,64 +1,100 @@
 // Basic module structure for RuneSwap Solver
 
 // Module declarations
+pub mod amount;
 pub mod types;
+pub mod bus;
 pub mod config;
+pub mod control;
+pub mod gas_oracle;
 pub mod runeswap;
+pub mod signer;
 pub mod solver;
+pub mod swap_state;
+pub mod transport;
 
 use std::error::Error;
+use std::sync::Arc;
 use crate::config::Config;
-use crate::runeswap::RuneSwapClient;
-use crate::solver::{NearIntentsSolver, Solver};
+use crate::control::ControlState;
+use crate::runeswap::{
+    GasOracleMiddleware, RetryMiddleware, RuneSwapClient, SignerMiddleware, SolverMiddleware,
+};
+use crate::signer::NearSigner;
+use crate::solver::NearIntentsSolver;
 
 /// Main entry point for the RuneSwap NEAR Intents integration
 pub struct RuneSwapSolver {
     /// Configuration for the solver
     pub config: Config,
-    
-    /// Client for interacting with RuneSwap API
-    pub runeswap_client: RuneSwapClient,
+
+    /// Middleware stack for interacting with the RuneSwap API
+    pub client: Arc<dyn SolverMiddleware>,
+
+    inner: NearIntentsSolver,
 }
 
 impl RuneSwapSolver {
     /// Create a new RuneSwap solver instance
     pub fn new(config: Config) -> Self {
-        let runeswap_client = RuneSwapClient::new(&config.runeswap_api_key);
+        let base: Arc<dyn SolverMiddleware> = Arc::new(RuneSwapClient::new(&config.runeswap_api_key));
+        let retried: Arc<dyn SolverMiddleware> = Arc::new(RetryMiddleware::new(base));
+
+        // Net gas fees out of quotes before they're checked against the
+        // intent's minimum, so a quote that was profitable before fees
+        // doesn't get signed and broadcast anyway.
+        let gas_oracle = gas_oracle::from_config(&config.gas_oracle);
+        let fee_checked: Arc<dyn SolverMiddleware> =
+            Arc::new(GasOracleMiddleware::new(retried, gas_oracle));
+
+        // Layer on a signer when the configured NEAR key can be parsed, so
+        // swaps are actually signed and broadcast rather than stubbed out.
+        let client: Arc<dyn SolverMiddleware> =
+            match NearSigner::from_private_key(&config.near_account_id, &config.near_private_key) {
+                Ok(signer) => Arc::new(SignerMiddleware::new(fee_checked, signer)),
+                Err(e) => {
+                    log::warn!("NEAR signer unavailable ({}), swaps will not be signed", e);
+                    fee_checked
+                }
+            };
+
+        let inner = NearIntentsSolver::new(
+            config.near_account_id.clone(),
+            config.near_private_key.clone(),
+            config.solver_bus_url.clone(),
+            client.clone(),
+        );
+
         Self {
             config,
-            runeswap_client,
+            client,
+            inner,
         }
     }
-    
+
     /// Initialize the solver with default configuration from environment variables
     pub fn init_default() -> Result<Self, Box<dyn Error>> {
         let config = Config::from_env()?;
         Ok(Self::new(config))
     }
-    
+
     /// Start the solver service
-    pub async fn start(&self) -> Result<(), Box<dyn Error>> {
+    pub async fn start(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
         log::info!("Starting RuneSwap solver for NEAR Intents");
-        
-        // Create the NEAR Intents solver
-        let solver = NearIntentsSolver::new(
-            self.config.near_account_id.clone(),
-            self.config.near_private_key.clone(),
-            self.config.solver_bus_url.clone(),
-            self.runeswap_client.clone(),
-        );
-        
-        // Start the solver
-        solver.start().await?;
-        
+
+        self.inner.start().await?;
+
         Ok(())
     }
-}
 
-/// Solver trait that will be implemented by different solver strategies
-pub trait Solver {
-    fn process_intent(&self) -> Result<(), Box<dyn std::error::Error>>;
-    fn execute_swap(&self) -> Result<(), Box<dyn std::error::Error>>;
+    /// Shared state handed to the embedded control server so operators
+    /// can inspect and drive this solver externally.
+    pub fn control_state(&self) -> Arc<ControlState> {
+        Arc::new(ControlState {
+            client: self.client.clone(),
+            swap_states: self.inner.swap_states(),
+        })
+    }
 }
 
 #[cfg(test)]
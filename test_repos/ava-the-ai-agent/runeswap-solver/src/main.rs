@@ -1,14 +1,19 @@
+use runeswap_solver::control::ControlServer;
 use runeswap_solver::RuneSwapSolver;
 use std::error::Error;
 use tokio::signal;
 
+/// Default bind address for the embedded control server when `--rpc` is
+/// passed without an explicit `--rpc-addr`.
+const DEFAULT_CONTROL_ADDR: &str = "127.0.0.1:9090";
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // Initialize logger
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
-    
+
     log::info!("RuneSwap Solver - NEAR Intents Integration");
-    
+
     // Initialize the solver with configuration from environment variables
     let solver = match RuneSwapSolver::init_default() {
         Ok(solver) => {
@@ -21,12 +26,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
             return Err(e);
         }
     };
-    
+
     // Log configuration details (with sensitive data masked)
     log::info!("Using RuneSwap API key: {}", mask_api_key(&solver.config.runeswap_api_key));
     log::info!("Using NEAR account ID: {}", solver.config.near_account_id);
     log::info!("Connecting to solver bus: {}", solver.config.solver_bus_url);
-    
+
+    // Optionally start the embedded control server for operator inspection
+    // and manual operation, behind --rpc (and --rpc-addr <addr>).
+    if let Some(addr) = control_addr_from_args(std::env::args()) {
+        let control_server = ControlServer::new(solver.control_state());
+        tokio::spawn(async move {
+            if let Err(e) = control_server.run(&addr).await {
+                log::error!("Control server error: {}", e);
+            }
+        });
+    }
+
     // Start the solver service in a separate task
     let solver_task = tokio::spawn(async move {
         if let Err(e) = solver.start().await {
@@ -40,7 +56,6 @@ async fn main() -> Result<(), Box<dyn Error>> {
     match signal::ctrl_c().await {
         Ok(()) => {
             log::info!("Shutdown signal received, closing solver...");
-            let _ = shutdown_tx.send(true); // Signal the solver to shut down
         },
         Err(e) => {
             log::error!("Failed to listen for shutdown signal: {}", e);
@@ -56,6 +71,25 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Parse `--rpc [--rpc-addr <addr>]` out of the process args, returning the
+/// address the control server should bind to, or `None` if `--rpc` wasn't
+/// passed.
+fn control_addr_from_args(args: impl Iterator<Item = String>) -> Option<String> {
+    let args: Vec<String> = args.collect();
+    if !args.iter().any(|a| a == "--rpc") {
+        return None;
+    }
+
+    let addr = args
+        .iter()
+        .position(|a| a == "--rpc-addr")
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_CONTROL_ADDR.to_string());
+
+    Some(addr)
+}
+
 // Utility function to mask API key for logging
 fn mask_api_key(api_key: &str) -> String {
     if api_key.len() <= 8 {
@@ -1,22 +1,82 @@
 // RuneSwap API client implementation
+//
+// The client is modeled as a stack of composable middleware layers, in the
+// spirit of ethers-rs' `Middleware` trait: a base layer talks directly to
+// the RuneSwap HTTP API, and optional layers (retries, gas pricing,
+// signing, ...) wrap it to add behavior without touching the base
+// implementation. Each layer forwards to `inner()` for anything it doesn't
+// override, so a stack like
+// `SignerMiddleware::new(GasOracleMiddleware::new(RetryMiddleware::new(base)))`
+// composes independently testable pieces instead of one monolithic client.
 
+use crate::amount::{AmountError, TokenAmount, U256};
+use crate::gas_oracle::{GasOracle, GasOracleError};
+use crate::signer::{NearSigner, SignerError};
 use crate::types::{SwapIntent, SwapQuote, Token};
-use reqwest::{Client, header};
+use async_trait::async_trait;
+use reqwest::{header, Client};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Client for interacting with the RuneSwap API
-#[derive(Clone)]
-pub struct RuneSwapClient {
-    /// HTTP client for API calls
-    client: Client,
-    
-    /// API key for authentication
-    api_key: String,
-    
-    /// Base URL for the RuneSwap API
-    base_url: String,
+/// Errors that can occur anywhere in the solver middleware stack.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("invalid API key header: {0}")]
+    InvalidHeader(#[from] header::InvalidHeaderValue),
+
+    #[error("invalid token amount: {0}")]
+    Amount(#[from] AmountError),
+
+    #[error("quote for {intent_id} would return {quoted} which is below the minimum of {minimum}")]
+    SlippageExceeded {
+        intent_id: String,
+        quoted: String,
+        minimum: String,
+    },
+
+    #[error("failed to sign intent: {0}")]
+    Signing(#[from] SignerError),
+
+    #[error("gas oracle error: {0}")]
+    GasOracle(#[from] GasOracleError),
+
+    #[error("middleware error: {0}")]
+    Middleware(String),
+}
+
+/// A layer in the solver's request pipeline.
+///
+/// Implementors either talk to the outside world directly (the base layer)
+/// or wrap another `SolverMiddleware` and override only the methods they
+/// care about, delegating the rest to `inner()`.
+#[async_trait]
+pub trait SolverMiddleware: Send + Sync {
+    /// Get a quote for a swap.
+    async fn get_quote(&self, intent: &SwapIntent) -> Result<SwapQuote, ClientError> {
+        self.inner().get_quote(intent).await
+    }
+
+    /// Execute a swap that fulfills `intent` with the previously obtained `quote`.
+    async fn execute_swap(
+        &self,
+        intent: &SwapIntent,
+        quote: &SwapQuote,
+    ) -> Result<String, ClientError> {
+        self.inner().execute_swap(intent, quote).await
+    }
+
+    /// Get supported tokens from the API.
+    async fn get_supported_tokens(&self) -> Result<Vec<Token>, ClientError> {
+        self.inner().get_supported_tokens().await
+    }
+
+    /// The next layer down the stack. The base layer returns itself, since
+    /// it overrides every method above and is never actually delegated to.
+    fn inner(&self) -> &dyn SolverMiddleware;
 }
 
 /// Response from the RuneSwap API for a quote
@@ -39,6 +99,19 @@ struct RuneSwapQuoteRequest {
     side: String, // "buy" or "sell"
 }
 
+/// Base middleware layer: talks directly to the RuneSwap HTTP API.
+#[derive(Clone)]
+pub struct RuneSwapClient {
+    /// HTTP client for API calls
+    client: Client,
+
+    /// API key for authentication
+    api_key: String,
+
+    /// Base URL for the RuneSwap API
+    base_url: String,
+}
+
 impl RuneSwapClient {
     /// Create a new RuneSwap client
     pub fn new(api_key: &str) -> Self {
@@ -48,26 +121,28 @@ impl RuneSwapClient {
             "x-api-key",
             header::HeaderValue::from_str(api_key).unwrap(),
         );
-        
+
         let client = Client::builder()
             .default_headers(headers)
             .build()
             .unwrap();
-            
+
         Self {
             client,
             api_key: api_key.to_string(),
             base_url: "https://api.runeswap.io/v1".to_string(),
         }
     }
-    
-    /// Get a quote for a swap
-    pub async fn get_quote(&self, intent: &SwapIntent) -> Result<SwapQuote, Box<dyn Error>> {
+}
+
+#[async_trait]
+impl SolverMiddleware for RuneSwapClient {
+    async fn get_quote(&self, intent: &SwapIntent) -> Result<SwapQuote, ClientError> {
         let url = format!("{}/quote", self.base_url);
-        
+
         // Determine if this is a buy or sell
         let side = "sell"; // Default to sell
-        
+
         // Create the request body
         let request = RuneSwapQuoteRequest {
             from_token: intent.from_token.address.clone(),
@@ -75,49 +150,68 @@ impl RuneSwapClient {
             amount: intent.amount.clone(),
             side: side.to_string(),
         };
-        
+
         // Send the request to the API
-        let response = self.client.post(&url)
+        let response = self
+            .client
+            .post(&url)
             .json(&request)
             .send()
             .await?
             .json::<RuneSwapQuoteResponse>()
             .await?;
-            
+
+        // Validate the quote against the intent's minimum before accepting it
+        let amount_out = TokenAmount::from_raw_str(&response.to_amount, &intent.to_token)?;
+        let min_amount_out = TokenAmount::from_raw_str(&intent.min_amount_out, &intent.to_token)?;
+
+        if !amount_out.meets_minimum(&min_amount_out) {
+            return Err(ClientError::SlippageExceeded {
+                intent_id: intent.id.clone(),
+                quoted: amount_out.to_decimal_string(),
+                minimum: min_amount_out.to_decimal_string(),
+            });
+        }
+
         // Convert the API response to our internal SwapQuote type
         let quote = SwapQuote {
             intent_id: intent.id.clone(),
             amount_out: response.to_amount,
             price: response.price,
             gas_estimate: response.gas_estimate,
+            slippage_bps: amount_out.slippage_bps(&min_amount_out).unwrap_or(0),
+            expires_at: response.expires_at,
         };
-        
+
         Ok(quote)
     }
-    
-    /// Execute a swap based on a quote
-    pub async fn execute_swap(&self, quote: &SwapQuote) -> Result<String, Box<dyn Error>> {
-        let url = format!("{}/execute", self.base_url);
-        
-        // In a real implementation, this would send the execution request to the API
+
+    async fn execute_swap(
+        &self,
+        _intent: &SwapIntent,
+        quote: &SwapQuote,
+    ) -> Result<String, ClientError> {
+        let _url = format!("{}/execute", self.base_url);
+
+        // The base layer has no signing key of its own; wrap it in a
+        // `SignerMiddleware` to actually broadcast a signed transaction.
         // For now, just log and return a placeholder transaction ID
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_millis();
-            
+
         let tx_id = format!("tx-{}", timestamp);
-        
+
         log::info!("Executed swap with quote ID: {}", quote.intent_id);
         log::info!("Transaction ID: {}", tx_id);
-        
+
         Ok(tx_id)
     }
-    
-    /// Get supported tokens from the API
-    pub async fn get_supported_tokens(&self) -> Result<Vec<Token>, Box<dyn Error>> {
-        let url = format!("{}/tokens", self.base_url);
-        
+
+    async fn get_supported_tokens(&self) -> Result<Vec<Token>, ClientError> {
+        let _url = format!("{}/tokens", self.base_url);
+
         // In a real implementation, this would fetch tokens from the API
         // For now, return some placeholder tokens
         let tokens = vec![
@@ -137,7 +231,241 @@ impl RuneSwapClient {
                 decimals: 24,
             },
         ];
-        
+
         Ok(tokens)
     }
-} 
\ No newline at end of file
+
+    fn inner(&self) -> &dyn SolverMiddleware {
+        self
+    }
+}
+
+/// Retries quote/swap requests a bounded number of times before giving up,
+/// so a transient HTTP failure doesn't fail the whole pipeline.
+pub struct RetryMiddleware {
+    inner: Arc<dyn SolverMiddleware>,
+    max_retries: u32,
+}
+
+impl RetryMiddleware {
+    /// Wrap `inner` with the default retry budget.
+    pub fn new(inner: Arc<dyn SolverMiddleware>) -> Self {
+        Self::with_max_retries(inner, 3)
+    }
+
+    /// Wrap `inner`, retrying up to `max_retries` times on failure.
+    pub fn with_max_retries(inner: Arc<dyn SolverMiddleware>, max_retries: u32) -> Self {
+        Self { inner, max_retries }
+    }
+}
+
+#[async_trait]
+impl SolverMiddleware for RetryMiddleware {
+    async fn get_quote(&self, intent: &SwapIntent) -> Result<SwapQuote, ClientError> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.get_quote(intent).await {
+                Ok(quote) => return Ok(quote),
+                Err(e) if attempt < self.max_retries => {
+                    attempt += 1;
+                    log::warn!(
+                        "get_quote failed (attempt {}/{}): {}",
+                        attempt,
+                        self.max_retries,
+                        e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn execute_swap(
+        &self,
+        intent: &SwapIntent,
+        quote: &SwapQuote,
+    ) -> Result<String, ClientError> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.execute_swap(intent, quote).await {
+                Ok(tx_id) => return Ok(tx_id),
+                Err(e) if attempt < self.max_retries => {
+                    attempt += 1;
+                    log::warn!(
+                        "execute_swap failed (attempt {}/{}): {}",
+                        attempt,
+                        self.max_retries,
+                        e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn inner(&self) -> &dyn SolverMiddleware {
+        self.inner.as_ref()
+    }
+}
+
+/// Adjusts quotes using a live gas/price oracle before they reach the
+/// solver: `amount_out` is netted down by `gas_estimate * gas_price`
+/// (paid in `from_token`'s currency), converted into `to_token` through
+/// the quote's exchange rate, and a quote that no longer meets
+/// `SwapIntent::min_amount_out` once fees are accounted for is rejected
+/// the same way an on-chain slippage violation would be.
+pub struct GasOracleMiddleware {
+    inner: Arc<dyn SolverMiddleware>,
+    oracle: Arc<dyn GasOracle>,
+}
+
+impl GasOracleMiddleware {
+    pub fn new(inner: Arc<dyn SolverMiddleware>, oracle: Arc<dyn GasOracle>) -> Self {
+        Self { inner, oracle }
+    }
+}
+
+#[async_trait]
+impl SolverMiddleware for GasOracleMiddleware {
+    async fn get_quote(&self, intent: &SwapIntent) -> Result<SwapQuote, ClientError> {
+        let mut quote = self.inner.get_quote(intent).await?;
+
+        let gas_price = self.oracle.gas_price().await?;
+        let fee = U256::from_u128(quote.gas_estimate as u128)
+            .checked_mul(U256::from_u128(gas_price as u128))
+            .ok_or(AmountError::Overflow)?;
+
+        // Gas is paid in the chain's native currency, i.e. `from_token` (the
+        // asset actually leaving the signer's wallet) — not necessarily
+        // `to_token`. Subtracting `fee` straight off `amount_out` silently
+        // assumed the two always matched, over- or under-charging the fee
+        // whenever they don't. Convert it into `to_token` terms through the
+        // quote's exchange rate before netting it out.
+        let fee_native = TokenAmount::new(fee, intent.from_token.decimals);
+        let fee_in_to_token = fee_native.convert_via_price(&quote.price, intent.to_token.decimals)?;
+
+        let amount_out = TokenAmount::from_raw_str(&quote.amount_out, &intent.to_token)?;
+        let min_amount_out = TokenAmount::from_raw_str(&intent.min_amount_out, &intent.to_token)?;
+        let net_amount_out = amount_out
+            .checked_sub(fee_in_to_token)
+            .unwrap_or(TokenAmount::new(U256::ZERO, intent.to_token.decimals));
+
+        if !net_amount_out.meets_minimum(&min_amount_out) {
+            return Err(ClientError::SlippageExceeded {
+                intent_id: intent.id.clone(),
+                quoted: net_amount_out.to_decimal_string(),
+                minimum: min_amount_out.to_decimal_string(),
+            });
+        }
+
+        quote.amount_out = net_amount_out.raw.to_string();
+        quote.slippage_bps = net_amount_out.slippage_bps(&min_amount_out).unwrap_or(0);
+        Ok(quote)
+    }
+
+    fn inner(&self) -> &dyn SolverMiddleware {
+        self.inner.as_ref()
+    }
+}
+
+/// Request body for broadcasting a signed intent to NEAR.
+#[derive(Debug, Serialize)]
+struct NearBroadcastRequest {
+    jsonrpc: String,
+    id: String,
+    method: String,
+    params: NearBroadcastParams,
+}
+
+#[derive(Debug, Serialize)]
+struct NearBroadcastParams {
+    signed_intent: String,
+    signature: String,
+    public_key: String,
+}
+
+/// Response from NEAR's transaction broadcast RPC.
+#[derive(Debug, Deserialize)]
+struct NearBroadcastResponse {
+    result: NearBroadcastResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct NearBroadcastResult {
+    transaction_hash: String,
+}
+
+/// Signs outgoing intents with the solver's NEAR account and broadcasts
+/// them, owning swap execution end-to-end. Quoting is still delegated
+/// down the stack.
+pub struct SignerMiddleware {
+    inner: Arc<dyn SolverMiddleware>,
+    signer: NearSigner,
+    near_rpc_client: Client,
+    near_rpc_url: String,
+}
+
+impl SignerMiddleware {
+    /// Wrap `inner`, broadcasting signed swaps to the default NEAR RPC endpoint.
+    pub fn new(inner: Arc<dyn SolverMiddleware>, signer: NearSigner) -> Self {
+        Self::with_rpc_url(inner, signer, "https://rpc.mainnet.near.org".to_string())
+    }
+
+    /// Wrap `inner`, broadcasting signed swaps to `near_rpc_url`.
+    pub fn with_rpc_url(
+        inner: Arc<dyn SolverMiddleware>,
+        signer: NearSigner,
+        near_rpc_url: String,
+    ) -> Self {
+        Self {
+            inner,
+            signer,
+            near_rpc_client: Client::new(),
+            near_rpc_url,
+        }
+    }
+}
+
+#[async_trait]
+impl SolverMiddleware for SignerMiddleware {
+    async fn execute_swap(
+        &self,
+        intent: &SwapIntent,
+        quote: &SwapQuote,
+    ) -> Result<String, ClientError> {
+        let message = self.signer.build_intent_message(intent, quote);
+        let signed = self.signer.sign(&message)?;
+
+        let request = NearBroadcastRequest {
+            jsonrpc: "2.0".to_string(),
+            id: intent.id.clone(),
+            method: "broadcast_tx_commit".to_string(),
+            params: NearBroadcastParams {
+                signed_intent: signed.payload,
+                signature: signed.signature,
+                public_key: signed.public_key,
+            },
+        };
+
+        let response = self
+            .near_rpc_client
+            .post(&self.near_rpc_url)
+            .json(&request)
+            .send()
+            .await?
+            .json::<NearBroadcastResponse>()
+            .await?;
+
+        log::info!(
+            "Broadcast swap for intent {} as tx {}",
+            intent.id,
+            response.result.transaction_hash
+        );
+
+        Ok(response.result.transaction_hash)
+    }
+
+    fn inner(&self) -> &dyn SolverMiddleware {
+        self.inner.as_ref()
+    }
+}
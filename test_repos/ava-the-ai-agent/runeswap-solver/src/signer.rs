@@ -0,0 +1,173 @@
+// NEAR ed25519 signing for outgoing Intents payloads.
+//
+// `RuneSwapClient::execute_swap` used to fabricate a `tx-{timestamp}` id
+// and never touched `Config::near_private_key`. `NearSigner` loads the
+// configured keypair, builds the canonical `token_diff` `IntentMessage`
+// for a fulfilled swap, and signs it so the solver can actually submit
+// intents instead of only logging them.
+
+use crate::types::{Intent, IntentDeadline, IntentMessage, SwapIntent, SwapQuote};
+use ed25519_dalek::{Signer, SigningKey};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// How long a built intent message stays valid before it needs a fresh
+/// deadline.
+const INTENT_VALIDITY_SECS: u64 = 300;
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("invalid NEAR private key: {0}")]
+    InvalidKey(String),
+
+    #[error("failed to serialize intent message: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Signs NEAR Intents payloads with the solver's ed25519 keypair.
+pub struct NearSigner {
+    account_id: String,
+    signing_key: SigningKey,
+}
+
+impl NearSigner {
+    /// Load a signer from a NEAR private key in `ed25519:<base58>` form,
+    /// the format `near-cli` writes to credentials files and that
+    /// `Config::near_private_key` is expected to hold.
+    pub fn from_private_key(account_id: &str, private_key: &str) -> Result<Self, SignerError> {
+        let encoded = private_key
+            .strip_prefix("ed25519:")
+            .ok_or_else(|| SignerError::InvalidKey("missing ed25519: prefix".to_string()))?;
+
+        let decoded = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|e| SignerError::InvalidKey(e.to_string()))?;
+
+        if decoded.len() != 64 {
+            return Err(SignerError::InvalidKey(
+                "expected a 64-byte ed25519 keypair".to_string(),
+            ));
+        }
+
+        // NEAR stores the 32-byte seed followed by the 32-byte public key.
+        let seed: [u8; 32] = decoded
+            .get(..32)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| {
+                SignerError::InvalidKey("expected a 64-byte ed25519 keypair".to_string())
+            })?;
+
+        Ok(Self {
+            account_id: account_id.to_string(),
+            signing_key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// Build the `token_diff` intent message fulfilling `intent` with
+    /// `quote`, with a deadline `INTENT_VALIDITY_SECS` in the future.
+    pub fn build_intent_message(&self, intent: &SwapIntent, quote: &SwapQuote) -> IntentMessage {
+        let mut diff = HashMap::new();
+        diff.insert(
+            intent.from_token.address.clone(),
+            format!("-{}", intent.amount),
+        );
+        diff.insert(intent.to_token.address.clone(), quote.amount_out.clone());
+
+        IntentMessage {
+            signer_id: self.account_id.clone(),
+            deadline: IntentDeadline {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + INTENT_VALIDITY_SECS,
+            },
+            intents: vec![Intent {
+                intent: "token_diff".to_string(),
+                diff,
+            }],
+        }
+    }
+
+    /// Serialize `message` to its canonical JSON payload and sign it.
+    pub fn sign(&self, message: &IntentMessage) -> Result<SignedIntent, SignerError> {
+        let payload = serde_json::to_string(message)?;
+        let signature = self.signing_key.sign(payload.as_bytes());
+
+        Ok(SignedIntent {
+            payload,
+            signature: bs58::encode(signature.to_bytes()).into_string(),
+            public_key: format!(
+                "ed25519:{}",
+                bs58::encode(self.signing_key.verifying_key().to_bytes()).into_string()
+            ),
+        })
+    }
+}
+
+/// A signed NEAR Intents payload, ready to submit to the solver bus or
+/// broadcast via NEAR RPC.
+#[derive(Debug, Clone)]
+pub struct SignedIntent {
+    pub payload: String,
+    pub signature: String,
+    pub public_key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Token;
+
+    fn test_signer() -> NearSigner {
+        // 64 zero bytes base58-encoded: a deterministic, non-secret test key.
+        let private_key = format!("ed25519:{}", bs58::encode([0u8; 64]).into_string());
+        NearSigner::from_private_key("solver.near", &private_key).unwrap()
+    }
+
+    #[test]
+    fn signs_intent_message_deterministically() {
+        let signer = test_signer();
+        let intent = SwapIntent {
+            id: "intent-1".to_string(),
+            from_token: Token {
+                symbol: "ETH".to_string(),
+                address: "0xETH".to_string(),
+                decimals: 18,
+            },
+            to_token: Token {
+                symbol: "USDC".to_string(),
+                address: "0xUSDC".to_string(),
+                decimals: 6,
+            },
+            amount: "1000000000000000000".to_string(),
+            min_amount_out: "1900000000".to_string(),
+            deadline: 1682661234,
+        };
+        let quote = SwapQuote {
+            intent_id: intent.id.clone(),
+            amount_out: "1950000000".to_string(),
+            price: "1950.0".to_string(),
+            gas_estimate: 21000,
+            slippage_bps: 263,
+            expires_at: 1682661534,
+        };
+
+        let message = signer.build_intent_message(&intent, &quote);
+        let signed_a = signer.sign(&message).unwrap();
+        let signed_b = signer.sign(&message).unwrap();
+
+        assert_eq!(signed_a.signature, signed_b.signature);
+        assert_eq!(signed_a.public_key, signed_b.public_key);
+    }
+
+    #[test]
+    fn rejects_private_key_without_prefix() {
+        assert!(NearSigner::from_private_key("solver.near", "not-a-key").is_err());
+    }
+}
@@ -1,31 +1,38 @@
 // Implementation of the NEAR Intents solver
 
-use crate::runeswap::RuneSwapClient;
-use crate::types::{
-    Intent, IntentDeadline, IntentMessage, JsonRpcRequest, JsonRpcResponse,
-    SolverBusMessage, SwapIntent, SwapQuote, SwapStatus,
-};
+use crate::bus::SolverBus;
+use crate::runeswap::SolverMiddleware;
+use crate::swap_state::{InMemorySwapStore, SwapState, SwapStateMachine};
+use crate::types::{SwapIntent, SwapQuote, SwapStatus};
 use async_trait::async_trait;
-use futures_util::{SinkExt, StreamExt};
-use std::collections::HashMap;
 use std::error::Error;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tokio::net::TcpStream;
-use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often to sweep tracked swaps for expired quotes/deadlines.
+const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(15);
 
 /// Solver trait for implementing different solver strategies
 #[async_trait]
 pub trait Solver {
-    async fn process_intent(&self, intent: &SwapIntent) -> Result<SwapQuote, Box<dyn Error>>;
-    async fn execute_swap(&self, quote: &SwapQuote) -> Result<String, Box<dyn Error>>;
+    async fn process_intent(
+        &self,
+        intent: &SwapIntent,
+    ) -> Result<SwapQuote, Box<dyn Error + Send + Sync>>;
+    async fn execute_swap(
+        &self,
+        quote: &SwapQuote,
+    ) -> Result<String, Box<dyn Error + Send + Sync>>;
 }
 
 /// Solver for NEAR Intents protocol
+#[derive(Clone)]
 pub struct NearIntentsSolver {
     account_id: String,
     private_key: String,
     solver_bus_url: String,
-    runeswap_client: RuneSwapClient,
+    client: Arc<dyn SolverMiddleware>,
+    swap_states: Arc<SwapStateMachine>,
 }
 
 impl NearIntentsSolver {
@@ -34,196 +41,129 @@ impl NearIntentsSolver {
         account_id: String,
         private_key: String,
         solver_bus_url: String,
-        runeswap_client: RuneSwapClient,
+        client: Arc<dyn SolverMiddleware>,
     ) -> Self {
         Self {
             account_id,
             private_key,
             solver_bus_url,
-            runeswap_client,
+            client,
+            swap_states: Arc::new(SwapStateMachine::new(Arc::new(
+                InMemorySwapStore::default(),
+            ))),
         }
     }
     
-    /// Start the solver and connect to the NEAR Intents bus
-    pub async fn start(&self) -> Result<(), Box<dyn Error>> {
-        log::info!("Connecting to solver bus at: {}", self.solver_bus_url);
-        
-        // Connect to the solver bus
-        let (ws_stream, _) = match connect_async(&self.solver_bus_url).await {
-            Ok(conn) => {
-                log::info!("Connected to solver bus");
-                conn
-            },
-            Err(e) => {
-                log::error!("Failed to connect to solver bus: {}", e);
-                return Err(Box::new(e));
+    /// Start the solver: subscribe to the NEAR Intents bus and process
+    /// each decoded intent as it arrives.
+    pub async fn start(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let bus = SolverBus::new(self.solver_bus_url.clone());
+        let (bus_handle, mut intents) = bus.subscribe();
+
+        let swap_states = self.swap_states.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(EXPIRY_SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                for intent_id in swap_states.expire_overdue() {
+                    log::warn!(
+                        "Swap {} expired before confirmation and needs an abort/refund",
+                        intent_id
+                    );
+                    // No refund or abort transaction is submitted here; the
+                    // swap is only marked `Expired`.
+                }
             }
-        };
-        
-        // Process messages from the bus
-        self.process_messages(ws_stream).await?;
-        
-        Ok(())
-    }
-    
-    /// Process messages from the WebSocket stream
-    async fn process_messages(
-        &self,
-        mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
-    ) -> Result<(), Box<dyn Error>> {
-        log::info!("Starting to process messages from solver bus");
-        
-        // Subscribe to intent messages using the JsonRpcRequest type
-        let subscribe_request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: 1,
-            method: "subscribe".to_string(),
-            params: Some(vec!["intents".to_string()]),
-        };
-        
-        // Convert the request to JSON and send it
-        let subscribe_json = serde_json::to_string(&subscribe_request)?;
-        ws_stream.send(Message::Text(subscribe_json)).await?;
-        
-        // Set up a simple ping/pong interval to keep the connection alive
-        let mut interval = tokio::time::interval(Duration::from_secs(30));
-        
-        loop {
-            tokio::select! {
-                // Handle WebSocket messages
-                msg = ws_stream.next() => {
-                    match msg {
-                        Some(Ok(Message::Text(text))) => {
-                            log::debug!("Received message: {}", text);
-                            
-                            // Try to parse the message as a SolverBusMessage
-                            match serde_json::from_str::<SolverBusMessage>(&text) {
-                                Ok(solver_msg) => {
-                                    if solver_msg.method == "subscription" {
-                                        if let Some(intent) = solver_msg.params.intent {
-                                            log::info!("Received swap intent: {} ({} -> {})", 
-                                                intent.id, 
-                                                intent.from_token.symbol, 
-                                                intent.to_token.symbol);
-                                                
-                                            // Process the intent and get a quote
-                                            match self.process_intent(&intent).await {
-                                                Ok(quote) => {
-                                                    log::info!("Generated quote for intent: {}", intent.id);
-                                                    
-                                                    // Send the quote response
-                                                    // In a real implementation, this would send the quote back to the bus
-                                                },
-                                                Err(e) => {
-                                                    log::error!("Failed to process intent: {}", e);
-                                                }
-                                            }
-                                        }
-                                    } else if solver_msg.method == "response" {
-                                        log::info!("Received response: {}", text);
-                                    }
-                                },
-                                Err(e) => {
-                                    // Try to parse as a JsonRpcResponse for subscription confirmation
-                                    match serde_json::from_str::<JsonRpcResponse>(&text) {
-                                        Ok(response) => {
-                                            log::info!("Subscription confirmed with ID: {}", response.result);
-                                        },
-                                        Err(_) => {
-                                            log::error!("Failed to parse message: {}", e);
-                                        }
-                                    }
-                                }
-                            }
-                        },
-                        Some(Ok(Message::Ping(data))) => {
-                            // Respond to ping with pong
-                            if let Err(e) = ws_stream.send(Message::Pong(data)).await {
-                                log::error!("Failed to send pong: {}", e);
-                                break;
+        });
+
+        log::info!("Starting to process intents from the solver bus");
+
+        // Each intent is quoted and published on its own spawned task so a
+        // slow RuneSwap/gas-oracle round trip for one intent can't hold up
+        // quoting the rest; nothing below depends on ordering between
+        // intents.
+        while let Some(intent) = intents.recv().await {
+            log::info!(
+                "Received swap intent: {} ({} -> {})",
+                intent.id,
+                intent.from_token.symbol,
+                intent.to_token.symbol
+            );
+
+            let solver = self.clone();
+            let bus_handle = bus_handle.clone();
+            tokio::spawn(async move {
+                match solver.process_intent(&intent).await {
+                    Ok(quote) => {
+                        log::info!("Generated quote for intent: {}", intent.id);
+                        match bus_handle.send_quote(&quote).await {
+                            Ok(response) => log::info!(
+                                "Solver bus acknowledged quote for {}: {}",
+                                intent.id,
+                                response.result
+                            ),
+                            Err(e) => {
+                                log::warn!("Failed to publish quote for {}: {}", intent.id, e)
                             }
-                        },
-                        Some(Ok(Message::Close(_))) => {
-                            log::info!("WebSocket connection closed by server");
-                            break;
-                        },
-                        Some(Err(e)) => {
-                            log::error!("WebSocket error: {}", e);
-                            break;
-                        },
-                        None => {
-                            log::error!("WebSocket stream ended unexpectedly");
-                            break;
-                        },
-                        _ => { /* Ignore other message types */ }
+                        }
                     }
-                },
-                
-                // Send ping periodically to keep connection alive
-                _ = interval.tick() => {
-                    log::trace!("Sending ping");
-                    if let Err(e) = ws_stream.send(Message::Ping(vec![])).await {
-                        log::error!("Failed to send ping: {}", e);
-                        break;
+                    Err(e) => {
+                        log::error!("Failed to process intent: {}", e);
                     }
                 }
-            }
+            });
         }
-        
-        log::info!("Stopped processing messages from solver bus");
+
+        log::info!("Solver bus subscription ended");
         Ok(())
     }
-    
+
+    /// The solver's swap lifecycle tracker, exposed so operator tooling
+    /// (e.g. the control server) can inspect in-flight swaps.
+    pub fn swap_states(&self) -> Arc<SwapStateMachine> {
+        self.swap_states.clone()
+    }
+
     /// Process an intent from the NEAR Intents protocol
-    pub async fn process_intent(&self, intent: &SwapIntent) -> Result<SwapQuote, Box<dyn Error>> {
+    pub async fn process_intent(
+        &self,
+        intent: &SwapIntent,
+    ) -> Result<SwapQuote, Box<dyn Error + Send + Sync>> {
         log::info!("Processing intent: {} ({} -> {})", 
             intent.id, 
             intent.from_token.symbol, 
             intent.to_token.symbol);
             
         // Get a quote from RuneSwap
-        let quote = self.runeswap_client.get_quote(intent).await?;
-        
-        log::info!("Quote received: amount_out={}, price={}", 
-            quote.amount_out, 
+        let quote = self.client.get_quote(intent).await?;
+
+        log::info!("Quote received: amount_out={}, price={}",
+            quote.amount_out,
             quote.price);
-            
-        // Create a token diff intent message
-        let deadline = IntentDeadline {
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs() + 300, // 5 minutes in the future
-        };
-        
-        // Create token diff for the swap (this would be used in a real implementation)
-        let mut diff = HashMap::new();
-        diff.insert(intent.from_token.address.clone(), format!("-{}", intent.amount));
-        diff.insert(intent.to_token.address.clone(), quote.amount_out.clone());
-        
-        let _intent_message = IntentMessage {
-            signer_id: self.account_id.clone(),
-            deadline,
-            intents: vec![Intent {
-                intent: "token_diff".to_string(),
-                diff,
-            }],
-        };
-        
-        // In a real implementation, this message would be signed and included in the quote response
-            
+
+        // Track the swap through its lifecycle; rejects quotes that are
+        // already past their expiry rather than executing them blind.
+        self.swap_states.track_quote(intent, quote.expires_at)?;
+
         Ok(quote)
     }
-    
-    /// Execute a swap based on a quote
-    pub async fn execute_swap(&self, quote: &SwapQuote) -> Result<String, Box<dyn Error>> {
+
+    /// Execute a swap that fulfills `intent` with the previously obtained `quote`
+    pub async fn execute_swap(
+        &self,
+        intent: &SwapIntent,
+        quote: &SwapQuote,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
         log::info!("Executing swap for intent: {}", quote.intent_id);
-        
-        // Execute the swap through RuneSwap
-        let tx_id = self.runeswap_client.execute_swap(quote).await?;
-        
+
+        self.swap_states.advance(&intent.id, SwapState::Locked)?;
+
+        // Sign and broadcast the swap through the middleware stack
+        let tx_id = self.client.execute_swap(intent, quote).await?;
+        self.swap_states.advance(&intent.id, SwapState::Broadcast)?;
+
         log::info!("Swap executed successfully: {}", tx_id);
-        
+
         Ok(tx_id)
     }
 } 
\ No newline at end of file
@@ -0,0 +1,324 @@
+// Atomic swap lifecycle state machine.
+//
+// `execute_swap` used to be fire-and-forget: once a quote was accepted
+// there was no record of whether the resulting swap ever confirmed,
+// expired, or needed a refund. `SwapStateMachine` tracks every fulfilled
+// intent through an explicit lifecycle (borrowed from the
+// quote/lock/broadcast/confirm model used by atomic swap daemons),
+// persists every transition to a `SwapStore` that is reloaded when the
+// machine is constructed, and watches the quote/intent deadlines so an
+// unconfirmed swap moves to `Expired` - reported by `expire_overdue` as
+// needing an abort/refund - instead of hanging.
+
+use crate::types::SwapIntent;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Lifecycle states for a fulfilled swap intent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapState {
+    /// A quote was obtained but the swap hasn't started executing.
+    Quoted,
+    /// Funds are locked against the quote (e.g. a token_diff intent signed).
+    Locked,
+    /// The signed transaction has been broadcast.
+    Broadcast,
+    /// The transaction confirmed on-chain.
+    Confirmed,
+    /// The quote or intent deadline passed before confirmation.
+    Expired,
+    /// An expired or failed swap's funds were returned.
+    Refunded,
+    /// The swap could not be completed and was not refundable.
+    Failed,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SwapStateError {
+    #[error("quote for intent {0} has already expired")]
+    QuoteExpired(String),
+
+    #[error("no swap tracked for intent {0}")]
+    UnknownIntent(String),
+
+    #[error("cannot transition intent {0} from {1:?} to {2:?}")]
+    InvalidTransition(String, SwapState, SwapState),
+}
+
+/// A persisted record of a single swap's lifecycle.
+#[derive(Debug, Clone)]
+pub struct SwapRecord {
+    pub intent_id: String,
+    pub state: SwapState,
+    pub intent_deadline: u64,
+    pub quote_expires_at: u64,
+}
+
+/// Where `SwapRecord`s are persisted; `SwapStateMachine::new` reloads them
+/// so in-flight swaps resume. `InMemorySwapStore`, the only implementation
+/// here, keeps records for the life of the process only, so they do not
+/// survive a process restart.
+pub trait SwapStore: Send + Sync {
+    fn save(&self, record: &SwapRecord);
+    fn load_all(&self) -> Vec<SwapRecord>;
+}
+
+#[derive(Default)]
+pub struct InMemorySwapStore {
+    records: Mutex<HashMap<String, SwapRecord>>,
+}
+
+impl SwapStore for InMemorySwapStore {
+    fn save(&self, record: &SwapRecord) {
+        self.records
+            .lock()
+            .unwrap()
+            .insert(record.intent_id.clone(), record.clone());
+    }
+
+    fn load_all(&self) -> Vec<SwapRecord> {
+        self.records.lock().unwrap().values().cloned().collect()
+    }
+}
+
+/// Drives fulfilled intents through their lifecycle, persisting every
+/// transition and watching deadlines for expiry.
+pub struct SwapStateMachine {
+    store: Arc<dyn SwapStore>,
+    records: Mutex<HashMap<String, SwapRecord>>,
+}
+
+impl SwapStateMachine {
+    /// Create a driver backed by `store`, reloading and resuming any
+    /// swaps persisted from a previous run.
+    pub fn new(store: Arc<dyn SwapStore>) -> Self {
+        let mut records = HashMap::new();
+        for record in store.load_all() {
+            log::info!(
+                "Resuming swap {} in state {:?}",
+                record.intent_id,
+                record.state
+            );
+            records.insert(record.intent_id.clone(), record);
+        }
+        Self {
+            store,
+            records: Mutex::new(records),
+        }
+    }
+
+    /// Begin tracking a freshly quoted intent, rejecting quotes that have
+    /// already expired.
+    pub fn track_quote(
+        &self,
+        intent: &SwapIntent,
+        quote_expires_at: u64,
+    ) -> Result<(), SwapStateError> {
+        if quote_expires_at <= now_secs() {
+            return Err(SwapStateError::QuoteExpired(intent.id.clone()));
+        }
+
+        self.persist(SwapRecord {
+            intent_id: intent.id.clone(),
+            state: SwapState::Quoted,
+            intent_deadline: intent.deadline,
+            quote_expires_at,
+        });
+        Ok(())
+    }
+
+    /// Advance a tracked swap to `to`, rejecting transitions that don't
+    /// follow the lifecycle.
+    pub fn advance(&self, intent_id: &str, to: SwapState) -> Result<(), SwapStateError> {
+        let mut records = self.records.lock().unwrap();
+        let record = records
+            .get_mut(intent_id)
+            .ok_or_else(|| SwapStateError::UnknownIntent(intent_id.to_string()))?;
+
+        if !is_valid_transition(record.state, to) {
+            return Err(SwapStateError::InvalidTransition(
+                intent_id.to_string(),
+                record.state,
+                to,
+            ));
+        }
+
+        record.state = to;
+        self.store.save(record);
+        Ok(())
+    }
+
+    /// Scan tracked swaps and move any whose quote or intent deadline has
+    /// passed before confirmation into `Expired`, returning the ids that
+    /// now need an abort/refund.
+    pub fn expire_overdue(&self) -> Vec<String> {
+        let now = now_secs();
+        let mut records = self.records.lock().unwrap();
+        let mut expired = Vec::new();
+
+        for record in records.values_mut() {
+            let overdue = now >= record.quote_expires_at || now >= record.intent_deadline;
+            let unsettled = !matches!(
+                record.state,
+                SwapState::Confirmed | SwapState::Refunded | SwapState::Failed | SwapState::Expired
+            );
+
+            if overdue && unsettled {
+                record.state = SwapState::Expired;
+                self.store.save(record);
+                expired.push(record.intent_id.clone());
+            }
+        }
+
+        expired
+    }
+
+    pub fn state_of(&self, intent_id: &str) -> Option<SwapState> {
+        self.records.lock().unwrap().get(intent_id).map(|r| r.state)
+    }
+
+    /// Snapshot every tracked swap, regardless of lifecycle state. Used by
+    /// operator tooling (e.g. the control server) to list in-flight swaps.
+    pub fn all(&self) -> Vec<SwapRecord> {
+        self.records.lock().unwrap().values().cloned().collect()
+    }
+
+    fn persist(&self, record: SwapRecord) {
+        self.store.save(&record);
+        self.records
+            .lock()
+            .unwrap()
+            .insert(record.intent_id.clone(), record);
+    }
+}
+
+fn is_valid_transition(from: SwapState, to: SwapState) -> bool {
+    use SwapState::*;
+    matches!(
+        (from, to),
+        (Quoted, Locked)
+            | (Locked, Broadcast)
+            | (Broadcast, Confirmed)
+            | (Quoted, Expired)
+            | (Locked, Expired)
+            | (Broadcast, Expired)
+            | (Expired, Refunded)
+            | (Expired, Failed)
+            | (Quoted, Failed)
+            | (Locked, Failed)
+            | (Broadcast, Failed)
+    )
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Token;
+
+    fn test_intent(deadline: u64) -> SwapIntent {
+        SwapIntent {
+            id: "intent-1".to_string(),
+            from_token: Token {
+                symbol: "ETH".to_string(),
+                address: "0xETH".to_string(),
+                decimals: 18,
+            },
+            to_token: Token {
+                symbol: "USDC".to_string(),
+                address: "0xUSDC".to_string(),
+                decimals: 6,
+            },
+            amount: "1000000000000000000".to_string(),
+            min_amount_out: "1900000000".to_string(),
+            deadline,
+        }
+    }
+
+    #[test]
+    fn rejects_already_expired_quotes() {
+        let machine = SwapStateMachine::new(Arc::new(InMemorySwapStore::default()));
+        let intent = test_intent(now_secs() + 600);
+
+        let result = machine.track_quote(&intent, now_secs() - 1);
+
+        assert_eq!(
+            result,
+            Err(SwapStateError::QuoteExpired("intent-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn advances_through_the_happy_path() {
+        let machine = SwapStateMachine::new(Arc::new(InMemorySwapStore::default()));
+        let intent = test_intent(now_secs() + 600);
+
+        machine.track_quote(&intent, now_secs() + 300).unwrap();
+        machine.advance("intent-1", SwapState::Locked).unwrap();
+        machine.advance("intent-1", SwapState::Broadcast).unwrap();
+        machine.advance("intent-1", SwapState::Confirmed).unwrap();
+
+        assert_eq!(machine.state_of("intent-1"), Some(SwapState::Confirmed));
+    }
+
+    #[test]
+    fn rejects_invalid_transitions() {
+        let machine = SwapStateMachine::new(Arc::new(InMemorySwapStore::default()));
+        let intent = test_intent(now_secs() + 600);
+        machine.track_quote(&intent, now_secs() + 300).unwrap();
+
+        let result = machine.advance("intent-1", SwapState::Confirmed);
+
+        assert_eq!(
+            result,
+            Err(SwapStateError::InvalidTransition(
+                "intent-1".to_string(),
+                SwapState::Quoted,
+                SwapState::Confirmed
+            ))
+        );
+    }
+
+    #[test]
+    fn expires_overdue_unsettled_swaps() {
+        let machine = SwapStateMachine::new(Arc::new(InMemorySwapStore::default()));
+        let intent = test_intent(now_secs() + 600);
+        machine.track_quote(&intent, now_secs() + 300).unwrap();
+        machine.advance("intent-1", SwapState::Locked).unwrap();
+
+        // Force the quote to look already-expired for the sweep.
+        machine.advance("intent-1", SwapState::Broadcast).unwrap();
+        {
+            let mut records = machine.records.lock().unwrap();
+            records.get_mut("intent-1").unwrap().quote_expires_at = now_secs() - 1;
+        }
+
+        let expired = machine.expire_overdue();
+
+        assert_eq!(expired, vec!["intent-1".to_string()]);
+        assert_eq!(machine.state_of("intent-1"), Some(SwapState::Expired));
+    }
+
+    #[test]
+    fn resumes_persisted_swaps_on_restart() {
+        let store = Arc::new(InMemorySwapStore::default());
+        {
+            let machine = SwapStateMachine::new(store.clone());
+            let intent = test_intent(now_secs() + 600);
+            machine.track_quote(&intent, now_secs() + 300).unwrap();
+            machine.advance("intent-1", SwapState::Locked).unwrap();
+        }
+
+        let resumed = SwapStateMachine::new(store);
+
+        assert_eq!(resumed.state_of("intent-1"), Some(SwapState::Locked));
+    }
+}
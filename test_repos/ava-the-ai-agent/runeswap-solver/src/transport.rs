@@ -0,0 +1,190 @@
+// Pluggable transport for the solver bus connection.
+//
+// `SolverBus` originally only spoke WebSocket, which forces a TCP/TLS hop
+// even when the solver and bus run on the same host. `Transport` abstracts
+// over "however we get framed `Message`s to and from the bus" so the
+// reconnect loop, ping/pong liveness, and subscribe handshake in `bus.rs`
+// stay the same no matter which implementation is underneath. The
+// concrete transport is picked from the scheme of `solver_bus_url` via
+// [`for_url`]:
+//   - `ws://` / `wss://`  -> `WebSocketTransport` (the original behavior)
+//   - `unix://<path>`     -> `IpcTransport` over a Unix domain socket
+//   - `npipe://<path>`    -> `IpcTransport` over a Windows named pipe
+// The IPC schemes point at a local path rather than a network address,
+// avoiding TCP/TLS overhead for co-located deployments.
+
+use async_trait::async_trait;
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use std::error::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+/// Concrete stream type `connect_async` yields for a `ws://`/`wss://` URL.
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// The read half of a live bus connection, decoded into
+/// `tungstenite::Message`s regardless of the underlying transport.
+#[async_trait]
+pub trait BusReader: Send {
+    async fn recv(&mut self) -> Option<Result<Message, Box<dyn Error + Send + Sync>>>;
+}
+
+/// The write half of a live bus connection.
+#[async_trait]
+pub trait BusWriter: Send {
+    async fn send(&mut self, msg: Message) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// Connects to the solver bus and yields its write/read halves, split up
+/// front so a writer task can own the write half while the reconnect
+/// loop's read loop runs independently (see `SolverBus::connect_and_process`).
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn connect(
+        &self,
+        url: &str,
+    ) -> Result<(Box<dyn BusWriter>, Box<dyn BusReader>), Box<dyn Error + Send + Sync>>;
+}
+
+/// Pick the transport implementation for `url`'s scheme. Defaults to
+/// WebSocket when the scheme isn't recognized, so existing `ws://`/`wss://`
+/// deployments are unaffected.
+pub fn for_url(url: &str) -> Box<dyn Transport> {
+    if url.starts_with("unix://") || url.starts_with("npipe://") {
+        Box::new(IpcTransport)
+    } else {
+        Box::new(WebSocketTransport)
+    }
+}
+
+/// The original transport: a `ws://`/`wss://` WebSocket connection.
+pub struct WebSocketTransport;
+
+#[async_trait]
+impl Transport for WebSocketTransport {
+    async fn connect(
+        &self,
+        url: &str,
+    ) -> Result<(Box<dyn BusWriter>, Box<dyn BusReader>), Box<dyn Error + Send + Sync>> {
+        let (ws_stream, _) = connect_async(url).await?;
+        let (write, read) = ws_stream.split();
+        Ok((Box::new(WsWriter { write }), Box::new(WsReader { read })))
+    }
+}
+
+struct WsWriter {
+    write: SplitSink<WsStream, Message>,
+}
+
+#[async_trait]
+impl BusWriter for WsWriter {
+    async fn send(&mut self, msg: Message) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.write.send(msg).await.map_err(Into::into)
+    }
+}
+
+struct WsReader {
+    read: SplitStream<WsStream>,
+}
+
+#[async_trait]
+impl BusReader for WsReader {
+    async fn recv(&mut self) -> Option<Result<Message, Box<dyn Error + Send + Sync>>> {
+        self.read.next().await.map(|r| r.map_err(Into::into))
+    }
+}
+
+/// A local-only transport over a Unix domain socket (`unix://<path>`) or,
+/// on Windows, a named pipe (`npipe://<path>`). Frames are newline-
+/// delimited JSON text, the same framing `control.rs` uses for its own
+/// embedded RPC server. Neither a Unix socket nor a named pipe has native
+/// control frames, so ping/pong is synthesized as sentinel text lines.
+pub struct IpcTransport;
+
+const PING_SENTINEL: &str = "__solver_bus_ping__";
+const PONG_SENTINEL: &str = "__solver_bus_pong__";
+
+#[async_trait]
+impl Transport for IpcTransport {
+    async fn connect(
+        &self,
+        url: &str,
+    ) -> Result<(Box<dyn BusWriter>, Box<dyn BusReader>), Box<dyn Error + Send + Sync>> {
+        #[cfg(unix)]
+        if let Some(path) = url.strip_prefix("unix://") {
+            let stream = tokio::net::UnixStream::connect(path).await?;
+            let (read, write) = tokio::io::split(stream);
+            return Ok((
+                Box::new(LineWriter { writer: write }),
+                Box::new(LineReader {
+                    reader: BufReader::new(read),
+                }),
+            ));
+        }
+
+        #[cfg(windows)]
+        if let Some(path) = url.strip_prefix("npipe://") {
+            let client = tokio::net::windows::named_pipe::ClientOptions::new().open(path)?;
+            let (read, write) = tokio::io::split(client);
+            return Ok((
+                Box::new(LineWriter { writer: write }),
+                Box::new(LineReader {
+                    reader: BufReader::new(read),
+                }),
+            ));
+        }
+
+        Err(format!("unsupported or unavailable IPC transport for url: {}", url).into())
+    }
+}
+
+/// Adapts the read half of a newline-delimited-JSON byte stream (a Unix
+/// socket or named pipe) to [`BusReader`]'s `Message`-based interface.
+struct LineReader<R> {
+    reader: BufReader<R>,
+}
+
+#[async_trait]
+impl<R: tokio::io::AsyncRead + Unpin + Send> BusReader for LineReader<R> {
+    async fn recv(&mut self) -> Option<Result<Message, Box<dyn Error + Send + Sync>>> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line).await {
+            Ok(0) => None,
+            Ok(_) => {
+                let line = line.trim_end();
+                Some(Ok(match line {
+                    PING_SENTINEL => Message::Ping(Vec::new()),
+                    PONG_SENTINEL => Message::Pong(Vec::new()),
+                    text => Message::Text(text.to_string()),
+                }))
+            }
+            Err(e) => Some(Err(Box::new(e))),
+        }
+    }
+}
+
+/// Adapts the write half of a newline-delimited-JSON byte stream to
+/// [`BusWriter`]'s `Message`-based interface.
+struct LineWriter<W> {
+    writer: W,
+}
+
+#[async_trait]
+impl<W: tokio::io::AsyncWrite + Unpin + Send> BusWriter for LineWriter<W> {
+    async fn send(&mut self, msg: Message) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let line = match msg {
+            Message::Text(text) => text,
+            Message::Ping(_) => PING_SENTINEL.to_string(),
+            Message::Pong(_) => PONG_SENTINEL.to_string(),
+            // Nothing meaningful to frame for a plain byte stream; the
+            // caller tears down the connection on close anyway.
+            Message::Close(_) => return Ok(()),
+            other => return Err(format!("unsupported frame for IPC transport: {:?}", other).into()),
+        };
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        Ok(())
+    }
+}
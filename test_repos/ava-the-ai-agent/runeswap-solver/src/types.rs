@@ -29,6 +29,13 @@ pub struct SwapQuote {
     pub amount_out: String,
     pub price: String,
     pub gas_estimate: u64,
+
+    /// How far `amount_out` exceeds the intent's `min_amount_out`, in
+    /// basis points of the minimum.
+    pub slippage_bps: i64,
+
+    /// Unix timestamp after which this quote is no longer executable.
+    pub expires_at: u64,
 }
 
 /// Status of a swap execution
@@ -0,0 +1,139 @@
+// Integration test for the embedded control server: boots it on an
+// ephemeral port and exercises every RPC method over a real TCP socket.
+
+use runeswap_solver::control::{ControlServer, ControlState};
+use runeswap_solver::runeswap::{RuneSwapClient, SolverMiddleware};
+use runeswap_solver::swap_state::{InMemorySwapStore, SwapStateMachine};
+use runeswap_solver::types::{SwapIntent, Token};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+
+async fn spawn_server() -> String {
+    let client: Arc<dyn SolverMiddleware> = Arc::new(RuneSwapClient::new("test_api_key"));
+    let swap_states = Arc::new(SwapStateMachine::new(Arc::new(
+        InMemorySwapStore::default(),
+    )));
+    let state = Arc::new(ControlState {
+        client,
+        swap_states,
+    });
+
+    // Bind on an ephemeral port ourselves so we know the address up front,
+    // then hand the already-bound listener's address to the server.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    let server = ControlServer::new(state);
+    let server_addr = addr.clone();
+    tokio::spawn(async move {
+        let _ = server.run(&server_addr).await;
+    });
+
+    // Give the listener a moment to come up.
+    for _ in 0..50 {
+        if TcpStream::connect(&addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    addr
+}
+
+async fn call(addr: &str, request: Value) -> Value {
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut line = serde_json::to_string(&request).unwrap();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await.unwrap();
+
+    let mut lines = BufReader::new(reader).lines();
+    let response = lines.next_line().await.unwrap().unwrap();
+    serde_json::from_str(&response).unwrap()
+}
+
+#[tokio::test]
+async fn get_status_reports_running() {
+    let addr = spawn_server().await;
+
+    let response = call(&addr, json!({"id": 1, "method": "get_status"})).await;
+
+    assert_eq!(response["result"]["status"], "running");
+}
+
+#[tokio::test]
+async fn list_active_intents_starts_empty() {
+    let addr = spawn_server().await;
+
+    let response = call(&addr, json!({"id": 1, "method": "list_active_intents"})).await;
+
+    assert_eq!(response["result"], json!([]));
+}
+
+#[tokio::test]
+async fn list_supported_tokens_returns_tokens() {
+    let addr = spawn_server().await;
+
+    let response = call(&addr, json!({"id": 1, "method": "list_supported_tokens"})).await;
+
+    let tokens = response["result"].as_array().unwrap();
+    assert!(tokens.iter().any(|t| t["symbol"] == "ETH"));
+}
+
+#[tokio::test]
+async fn unknown_method_returns_an_error() {
+    let addr = spawn_server().await;
+
+    let response = call(&addr, json!({"id": 1, "method": "not_a_real_method"})).await;
+
+    assert!(response["error"].is_string());
+}
+
+#[tokio::test]
+async fn get_quote_rejects_malformed_params() {
+    let addr = spawn_server().await;
+
+    // An intent missing its tokens and amounts should produce a JSON-RPC
+    // error, not a crash.
+    let response = call(
+        &addr,
+        json!({"id": 1, "method": "get_quote", "params": {"id": "intent-1"}}),
+    )
+    .await;
+
+    assert!(response["error"].is_string());
+}
+
+#[tokio::test]
+async fn execute_swap_rejects_malformed_params() {
+    let addr = spawn_server().await;
+    let intent = SwapIntent {
+        id: "intent-1".to_string(),
+        from_token: Token {
+            symbol: "ETH".to_string(),
+            address: "0xETH".to_string(),
+            decimals: 18,
+        },
+        to_token: Token {
+            symbol: "USDC".to_string(),
+            address: "0xUSDC".to_string(),
+            decimals: 6,
+        },
+        amount: "1000000000000000000".to_string(),
+        min_amount_out: "1900000000".to_string(),
+        deadline: 1682661234,
+    };
+
+    // Missing `quote` should produce a JSON-RPC error, not a crash.
+    let response = call(
+        &addr,
+        json!({"id": 1, "method": "execute_swap", "params": {"intent": intent}}),
+    )
+    .await;
+
+    assert!(response["error"].is_string());
+}